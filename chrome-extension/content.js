@@ -3,7 +3,11 @@
 
 const TARGET_SELECTOR = '[data-testid="issue.views.issue-base.foundation.breadcrumbs.breadcrumb-current-issue-container"]';
 const ISSUE_LINK_SELECTOR = '[data-testid="issue.views.issue-base.foundation.breadcrumbs.current-issue.item"]';
+const SUMMARY_SELECTOR = '[data-testid="issue.views.issue-base.foundation.summary.heading"]';
+const DESCRIPTION_SELECTOR = '[data-testid="issue.views.field.rich-text.description"]';
 const CUSTOM_DIV_ID = 'jira-issue-helper-div';
+// Server limit on ticket descriptions, in characters
+const MAX_DESCRIPTION_LENGTH = 4000;
 
 let statusPollInterval = null;
 let currentStatus = null;
@@ -38,6 +42,22 @@ function getIssueNumber() {
   return null;
 }
 
+// Get the issue summary (title) from the heading
+function getIssueSummary() {
+  const heading = document.querySelector(SUMMARY_SELECTOR);
+  return heading ? heading.textContent.trim() || null : null;
+}
+
+// Get the issue description as plain text, if the page shows one
+function getIssueDescription() {
+  const field = document.querySelector(DESCRIPTION_SELECTOR);
+  if (!field) {
+    return null;
+  }
+  const text = field.innerText.trim();
+  return text ? text.slice(0, MAX_DESCRIPTION_LENGTH) : null;
+}
+
 // Clear the results display
 function clearResults() {
   const resultsDiv = document.getElementById('poker-results-display');
@@ -57,6 +77,10 @@ async function startVoting() {
   try {
     const data = await apiRequest('/api/start-voting', 'POST', {
       issue_number: issueNumber || null,
+      title: getIssueSummary(),
+      description: getIssueDescription(),
+      url: window.location.href,
+      source: 'jira',
     });
     console.log('[Jira Issue Helper] Start voting response:', data);
     updateButtonStates();
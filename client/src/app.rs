@@ -1,13 +1,158 @@
-use common::{GameState, Phase, Role, VotingConfig};
+use common::{AdminCommand, ClientPayload, GameState, Phase, Player, Role, ServerInfo, Ticket};
 use uuid::Uuid;
-use tui_input::Input;
-use rand::Rng;
+use tui_input::{Input, InputRequest};
 
 pub enum CurrentScreen {
     Login,
     Main,
 }
 
+// Timers offered in the start-round dialog; `None` is a round without one.
+const TIMEOUT_CHOICES: [Option<u64>; 7] = [None, Some(15), Some(20), Some(30), Some(60), Some(120), Some(300)];
+
+// A dialog on top of the room; it takes all key presses while open.
+pub enum Modal {
+    StartRound(StartRoundForm),
+    // Reset clears the round for everyone, so it asks first
+    ConfirmReset,
+    // Index into the deck of the highlighted card
+    CardPicker { selected: usize },
+    // Opened by clicking a name in the players panel
+    PlayerDetails(Uuid),
+}
+
+// How long to wait for a second digit when the typed one could start a longer card ("1" vs "13").
+pub const DIGIT_WAIT: std::time::Duration = std::time::Duration::from_millis(700);
+
+#[derive(Debug, PartialEq)]
+pub enum DigitMatch {
+    Card(u32),
+    // Could still become a card with more digits
+    Pending,
+    NoMatch,
+}
+
+// Match typed digits against the deck. A card is chosen as soon as no other
+// card starts with the same digits.
+pub fn match_digits(cards: &[u32], typed: &str) -> DigitMatch {
+    let longer = cards.iter().any(|c| {
+        let c = c.to_string();
+        c.len() > typed.len() && c.starts_with(typed)
+    });
+    let exact = cards.iter().copied().find(|c| c.to_string() == typed);
+    match (exact, longer) {
+        (Some(card), false) => DigitMatch::Card(card),
+        (_, true) => DigitMatch::Pending,
+        (None, false) => DigitMatch::NoMatch,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StartField {
+    Title,
+    Timeout,
+    Backlog,
+}
+
+pub struct StartRoundForm {
+    pub focus: StartField,
+    pub title: Input,
+    pub timeout: Option<u64>,
+    // Highlighted backlog entry; used when the round starts from the backlog
+    pub backlog_selected: usize,
+}
+
+impl StartRoundForm {
+    pub fn new(default_timeout: Option<u64>) -> Self {
+        Self {
+            focus: StartField::Title,
+            title: Input::default(),
+            timeout: default_timeout,
+            backlog_selected: 0,
+        }
+    }
+
+    // The backlog field is skipped when there is nothing in it.
+    pub fn next_field(&mut self, has_backlog: bool, forward: bool) {
+        let fields: &[StartField] = if has_backlog {
+            &[StartField::Title, StartField::Timeout, StartField::Backlog]
+        } else {
+            &[StartField::Title, StartField::Timeout]
+        };
+        let at = fields.iter().position(|f| *f == self.focus).unwrap_or(0);
+        let next = if forward { at + 1 } else { at + fields.len() - 1 };
+        self.focus = fields[next % fields.len()];
+    }
+
+    // Step through the preset timers; a configured default that isn't a preset stays reachable.
+    pub fn cycle_timeout(&mut self, default_timeout: Option<u64>, forward: bool) {
+        let mut choices = TIMEOUT_CHOICES.to_vec();
+        if !choices.contains(&default_timeout) {
+            choices.push(default_timeout);
+            choices.sort();
+        }
+        let at = choices.iter().position(|c| *c == self.timeout).unwrap_or(0);
+        let next = if forward { at + 1 } else { at + choices.len() - 1 };
+        self.timeout = choices[next % choices.len()];
+    }
+
+    pub fn move_selection(&mut self, backlog_len: usize, down: bool) {
+        if backlog_len == 0 {
+            return;
+        }
+        self.backlog_selected = if down {
+            (self.backlog_selected + 1).min(backlog_len - 1)
+        } else {
+            self.backlog_selected.saturating_sub(1)
+        };
+    }
+
+    // With the backlog focused, the highlighted ticket; otherwise whatever was typed.
+    pub fn command(&self, backlog: &[Ticket]) -> AdminCommand {
+        let ticket = match self.focus {
+            StartField::Backlog => backlog.get(self.backlog_selected).cloned(),
+            _ => Some(self.title.value().trim())
+                .filter(|t| !t.is_empty())
+                .map(Ticket::new),
+        };
+        AdminCommand::StartVote { ticket, timeout: self.timeout }
+    }
+}
+
+// What the user asked for on the main screen, whichever key or click it came from.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Action {
+    // One step with the arrow keys
+    Step { dx: i8, dy: i8 },
+    // A click on the map
+    MoveTo(u16, u16),
+    ToggleConfirm,
+    TypeDigit(char),
+    // Enter: vote for whatever has been typed so far
+    SubmitDigits,
+    // The user stopped typing for `DIGIT_WAIT`
+    DigitsTimedOut,
+    OpenCardPicker,
+    OpenStartRound,
+    Reveal,
+    AskReset,
+    ScrollTicket { down: bool, max: u16 },
+    ScrollLog { up: bool },
+    ShowPlayer(Uuid),
+    // The rest act on the open dialog and do nothing without it
+    CloseModal,
+    ConfirmReset,
+    MovePickerSelection { forward: bool },
+    PickSelectedCard,
+    FocusStartField { forward: bool },
+    CycleStartTimeout { forward: bool },
+    MoveBacklogSelection { down: bool },
+    EditStartTitle(InputRequest),
+    SubmitStartRound,
+}
+
+const MAX_LOGS: usize = 200;
+
 pub struct App {
     pub current_screen: CurrentScreen,
     pub server_url: String, // Input for server URL
@@ -18,18 +163,25 @@ pub struct App {
     
     pub game_state: Option<GameState>,
     pub self_id: Option<Uuid>,
+    // Kept across disconnects so logging in again resumes the same player
+    pub session_token: Option<Uuid>,
+    pub server_info: Option<ServerInfo>,
 
-    // For movement input
-    pub last_known_pos: (u16, u16),
-    
     // Logs
     pub logs: Vec<String>,
+    // Lines scrolled back from the newest log entry
+    pub log_scroll: usize,
     
-    // Limits
-    pub grid_width: u16,
-    pub grid_height: u16,
-
     pub connection_error: Option<String>,
+
+    // Lines scrolled off the top of the ticket panel
+    pub ticket_scroll: u16,
+
+    pub modal: Option<Modal>,
+
+    // Digits typed towards a card value, and when the last one came in
+    pub typed_digits: String,
+    pub typed_at: Option<std::time::Instant>,
 }
 
 impl App {
@@ -43,19 +195,487 @@ impl App {
             symbol_input: common::AvatarSymbol::random(),
             game_state: None,
             self_id: None,
-            last_known_pos: (10, 10),
+            session_token: None,
+            server_info: None,
             logs: Vec::new(),
-            grid_width: 40, // Default fallback
-            grid_height: 20, 
+            log_scroll: 0,
             connection_error: None,
+            ticket_scroll: 0,
+            modal: None,
+            typed_digits: String::new(),
+            typed_at: None,
         }
     }
     
+    // Our own player, once the server has welcomed us.
+    pub fn me(&self) -> Option<&Player> {
+        self.self_id.and_then(|id| self.game_state.as_ref()?.players.get(&id))
+    }
+
+    pub fn is_scrum_master(&self) -> bool {
+        self.me().is_some_and(|p| p.role == Role::ScrumMaster)
+    }
+
+    // Apply an action to the local state and return the messages to send.
+    // Nothing here touches the terminal or the socket.
+    pub fn update(&mut self, action: Action) -> Vec<ClientPayload> {
+        match action {
+            Action::Step { dx, dy } => {
+                let (Some(state), Some(me)) = (&self.game_state, self.me()) else {
+                    return Vec::new();
+                };
+                // A confirmed vote stays put until it's unconfirmed
+                if me.confirmed {
+                    return Vec::new();
+                }
+                let (x, y) = me.position;
+                let (Some(x), Some(y)) = (x.checked_add_signed(dx.into()), y.checked_add_signed(dy.into())) else {
+                    return Vec::new();
+                };
+                if !state.map.contains(x, y) {
+                    return Vec::new();
+                }
+                vec![ClientPayload::Move { x, y }]
+            }
+            Action::MoveTo(x, y) => self.place_avatar(x, y),
+            Action::ToggleConfirm => {
+                let Some(confirmed) = self.me().map(|p| !p.confirmed) else {
+                    return Vec::new();
+                };
+                self.log(format!("Toggled confirmed: {}", confirmed));
+                // Confirming also votes for the zone we're standing in
+                vec![ClientPayload::VoteConfirm { confirmed }]
+            }
+            Action::TypeDigit(c) => {
+                self.typed_digits.push(c);
+                self.typed_at = Some(std::time::Instant::now());
+                match match_digits(&self.cards(), &self.typed_digits) {
+                    DigitMatch::Card(card) => {
+                        self.typed_digits.clear();
+                        self.typed_at = None;
+                        self.vote_for_card(card)
+                    }
+                    DigitMatch::Pending => Vec::new(),
+                    DigitMatch::NoMatch => {
+                        self.log(format!("No {} card in this deck", self.typed_digits));
+                        self.typed_digits.clear();
+                        self.typed_at = None;
+                        Vec::new()
+                    }
+                }
+            }
+            Action::SubmitDigits => {
+                self.typed_at = None;
+                let typed = std::mem::take(&mut self.typed_digits);
+                match typed.parse::<u32>() {
+                    Ok(card) => self.vote_for_card(card),
+                    Err(_) => Vec::new(),
+                }
+            }
+            // A lone "1" in a deck with 13 counts as 1 once the user stops typing
+            Action::DigitsTimedOut => {
+                self.typed_at = None;
+                let typed = std::mem::take(&mut self.typed_digits);
+                match typed.parse::<u32>().ok().filter(|c| self.cards().contains(c)) {
+                    Some(card) => self.vote_for_card(card),
+                    None => Vec::new(),
+                }
+            }
+            Action::OpenCardPicker => {
+                let cards = self.cards();
+                let mine = self.game_state.as_ref()
+                    .zip(self.self_id)
+                    .and_then(|(s, id)| s.votes.get(&id).copied().flatten());
+                let selected = mine.and_then(|v| cards.iter().position(|c| *c == v)).unwrap_or(0);
+                self.modal = Some(Modal::CardPicker { selected });
+                Vec::new()
+            }
+            Action::OpenStartRound => {
+                if self.is_scrum_master() {
+                    let default_timeout = self.game_state.as_ref().and_then(|s| s.config.default_timeout);
+                    self.modal = Some(Modal::StartRound(StartRoundForm::new(default_timeout)));
+                } else {
+                    self.log("Only the Scrum Master can start a round".to_string());
+                }
+                Vec::new()
+            }
+            Action::Reveal => {
+                if self.is_scrum_master() {
+                    self.admin(AdminCommand::Reveal)
+                } else {
+                    self.log("Only the Scrum Master can reveal the votes".to_string());
+                    Vec::new()
+                }
+            }
+            Action::AskReset => {
+                if self.is_scrum_master() {
+                    self.modal = Some(Modal::ConfirmReset);
+                } else {
+                    self.log("Only the Scrum Master can reset the round".to_string());
+                }
+                Vec::new()
+            }
+            Action::ScrollTicket { down, max } => {
+                self.ticket_scroll = if down {
+                    (self.ticket_scroll + 5).min(max)
+                } else {
+                    self.ticket_scroll.saturating_sub(5)
+                };
+                Vec::new()
+            }
+            Action::ScrollLog { up } => {
+                self.log_scroll = if up {
+                    (self.log_scroll + 1).min(self.logs.len().saturating_sub(1))
+                } else {
+                    self.log_scroll.saturating_sub(1)
+                };
+                Vec::new()
+            }
+            Action::ShowPlayer(id) => {
+                self.modal = Some(Modal::PlayerDetails(id));
+                Vec::new()
+            }
+            Action::CloseModal => {
+                self.modal = None;
+                Vec::new()
+            }
+            Action::ConfirmReset => {
+                if !matches!(self.modal, Some(Modal::ConfirmReset)) {
+                    return Vec::new();
+                }
+                self.modal = None;
+                self.admin(AdminCommand::Reset)
+            }
+            Action::MovePickerSelection { forward } => {
+                let last = self.cards().len().saturating_sub(1);
+                if let Some(Modal::CardPicker { selected }) = &mut self.modal {
+                    *selected = if forward { (*selected + 1).min(last) } else { selected.saturating_sub(1) };
+                }
+                Vec::new()
+            }
+            Action::PickSelectedCard => {
+                let Some(Modal::CardPicker { selected }) = self.modal else {
+                    return Vec::new();
+                };
+                self.modal = None;
+                match self.cards().get(selected).copied() {
+                    Some(card) => self.vote_for_card(card),
+                    None => Vec::new(),
+                }
+            }
+            Action::FocusStartField { forward } => {
+                let has_backlog = !self.backlog().is_empty();
+                if let Some(Modal::StartRound(form)) = &mut self.modal {
+                    form.next_field(has_backlog, forward);
+                }
+                Vec::new()
+            }
+            Action::CycleStartTimeout { forward } => {
+                let default_timeout = self.game_state.as_ref().and_then(|s| s.config.default_timeout);
+                if let Some(Modal::StartRound(form)) = &mut self.modal {
+                    form.cycle_timeout(default_timeout, forward);
+                }
+                Vec::new()
+            }
+            Action::MoveBacklogSelection { down } => {
+                let backlog_len = self.backlog().len();
+                if let Some(Modal::StartRound(form)) = &mut self.modal {
+                    form.move_selection(backlog_len, down);
+                }
+                Vec::new()
+            }
+            Action::EditStartTitle(request) => {
+                if let Some(Modal::StartRound(form)) = &mut self.modal {
+                    form.title.handle(request);
+                }
+                Vec::new()
+            }
+            Action::SubmitStartRound => {
+                let Some(Modal::StartRound(form)) = &self.modal else {
+                    return Vec::new();
+                };
+                let cmd = form.command(self.backlog());
+                self.modal = None;
+                self.admin(cmd)
+            }
+        }
+    }
+
+    fn admin(&mut self, cmd: AdminCommand) -> Vec<ClientPayload> {
+        self.log(format!("Admin: {}", cmd.kind()));
+        vec![ClientPayload::Admin(cmd)]
+    }
+
+    fn backlog(&self) -> &[Ticket] {
+        self.game_state.as_ref().map(|s| s.backlog.as_slice()).unwrap_or_default()
+    }
+
+    fn cards(&self) -> Vec<u32> {
+        self.game_state.as_ref().map(|s| s.config.cards.clone()).unwrap_or_default()
+    }
+
+    // Clicking the map puts the avatar on that cell; the server counts it as a
+    // vote if it's inside a zone.
+    fn place_avatar(&mut self, x: u16, y: u16) -> Vec<ClientPayload> {
+        let Some(state) = &self.game_state else {
+            return Vec::new();
+        };
+        if !state.map.contains(x, y) {
+            return Vec::new();
+        }
+        if self.me().is_some_and(|p| p.confirmed) {
+            self.log("Unconfirm (Space) before moving".to_string());
+            return Vec::new();
+        }
+        vec![ClientPayload::Move { x, y }]
+    }
+
+    // Vote without walking: put the avatar in the card's zone, just as if the
+    // player had walked there.
+    fn vote_for_card(&mut self, card: u32) -> Vec<ClientPayload> {
+        let Some(state) = &self.game_state else {
+            return Vec::new();
+        };
+        if !matches!(state.phase, Phase::Voting { .. }) {
+            self.log("No vote in progress".to_string());
+            return Vec::new();
+        }
+        if self.me().is_some_and(|p| p.confirmed) {
+            self.log("Unconfirm (Space) before changing your vote".to_string());
+            return Vec::new();
+        }
+        let Some(zone) = state.map.zone_for_card(card) else {
+            return Vec::new();
+        };
+        let (x, y) = zone.center();
+        self.log(format!("Voted {}", card));
+        vec![ClientPayload::Move { x, y }]
+    }
+
     pub fn log(&mut self, msg: String) {
-        // Keep last 10 logs
-        if self.logs.len() >= 10 {
+        // Keep the last MAX_LOGS; the log panel scrolls back through them
+        if self.logs.len() >= MAX_LOGS {
             self.logs.remove(0);
         }
+        // While scrolled back, keep showing the same lines
+        if self.log_scroll > 0 {
+            self.log_scroll = (self.log_scroll + 1).min(self.logs.len());
+        }
         self.logs.push(format!("{} - {}", common::current_time_unix(), msg));
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::{AvatarColor, AvatarSymbol, MapLayout, Presence, VotingConfig};
+    use std::collections::HashMap;
+
+    // A welcomed participant standing at `position`, in a room that is voting.
+    fn app_at(position: (u16, u16)) -> App {
+        let id = Uuid::new_v4();
+        let player = Player {
+            id,
+            name: "Alice".into(),
+            role: Role::Participant,
+            position,
+            color: AvatarColor::Red,
+            symbol: AvatarSymbol::Cat,
+            confirmed: false,
+            presence: Presence::Active,
+        };
+        let mut app = App::new();
+        app.self_id = Some(id);
+        app.game_state = Some(GameState {
+            players: HashMap::from([(id, player)]),
+            phase: Phase::Voting { start_time_unix: 0, duration_secs: None },
+            current_ticket: None,
+            votes: HashMap::new(),
+            final_estimate: None,
+            backlog: Vec::new(),
+            config: VotingConfig::default(),
+            map: MapLayout::default(),
+        });
+        app.current_screen = CurrentScreen::Main;
+        app
+    }
+
+    fn me_mut(app: &mut App) -> &mut Player {
+        let id = app.self_id.unwrap();
+        app.game_state.as_mut().unwrap().players.get_mut(&id).unwrap()
+    }
+
+    fn zone_center(app: &App, card: u32) -> (u16, u16) {
+        app.game_state.as_ref().unwrap().map.zone_for_card(card).unwrap().center()
+    }
+
+    #[test]
+    fn steps_stay_inside_the_grid() {
+        let mut app = app_at((0, 0));
+        assert!(app.update(Action::Step { dx: -1, dy: 0 }).is_empty());
+        assert!(app.update(Action::Step { dx: 0, dy: -1 }).is_empty());
+        assert!(matches!(app.update(Action::Step { dx: 1, dy: 0 })[..], [ClientPayload::Move { x: 1, y: 0 }]));
+
+        let map = app.game_state.as_ref().unwrap().map.clone();
+        let mut app = app_at((map.width - 1, map.height - 1));
+        assert!(app.update(Action::Step { dx: 1, dy: 0 }).is_empty());
+        assert!(app.update(Action::Step { dx: 0, dy: 1 }).is_empty());
+    }
+
+    #[test]
+    fn input_before_the_welcome_is_ignored() {
+        let mut app = App::new();
+        for action in [Action::Step { dx: 1, dy: 0 }, Action::ToggleConfirm, Action::TypeDigit('5'), Action::MoveTo(3, 3)] {
+            assert!(app.update(action).is_empty());
+        }
+    }
+
+    #[test]
+    fn a_confirmed_vote_locks_the_avatar() {
+        let mut app = app_at((5, 5));
+        me_mut(&mut app).confirmed = true;
+        assert!(app.update(Action::Step { dx: 1, dy: 0 }).is_empty());
+        assert!(app.update(Action::MoveTo(6, 6)).is_empty());
+        assert!(app.update(Action::TypeDigit('5')).is_empty());
+
+        let out = app.update(Action::ToggleConfirm);
+        assert!(matches!(out[..], [ClientPayload::VoteConfirm { confirmed: false }]));
+    }
+
+    #[test]
+    fn voting_by_value_walks_into_the_zone() {
+        let mut app = app_at((1, 1));
+        let (x, y) = zone_center(&app, 5);
+        let out = app.update(Action::TypeDigit('5'));
+        assert!(matches!(out[..], [ClientPayload::Move { x: mx, y: my }] if (mx, my) == (x, y)));
+    }
+
+    #[test]
+    fn a_prefix_of_a_longer_card_waits_for_more_digits() {
+        let mut app = app_at((1, 1));
+        assert!(app.update(Action::TypeDigit('1')).is_empty());
+        let (x, y) = zone_center(&app, 13);
+        let out = app.update(Action::TypeDigit('3'));
+        assert!(matches!(out[..], [ClientPayload::Move { x: mx, y: my }] if (mx, my) == (x, y)));
+
+        assert!(app.update(Action::TypeDigit('1')).is_empty());
+        let (x, y) = zone_center(&app, 1);
+        let out = app.update(Action::DigitsTimedOut);
+        assert!(matches!(out[..], [ClientPayload::Move { x: mx, y: my }] if (mx, my) == (x, y)));
+        assert!(app.typed_digits.is_empty());
+    }
+
+    #[test]
+    fn no_voting_outside_a_round() {
+        let mut app = app_at((1, 1));
+        app.game_state.as_mut().unwrap().phase = Phase::Idle;
+        assert!(app.update(Action::TypeDigit('5')).is_empty());
+        // Walking is still fine
+        assert_eq!(app.update(Action::MoveTo(3, 2)).len(), 1);
+    }
+
+    #[test]
+    fn clicks_off_the_map_are_ignored() {
+        let mut app = app_at((1, 1));
+        let width = app.game_state.as_ref().unwrap().map.width;
+        assert!(app.update(Action::MoveTo(width, 0)).is_empty());
+    }
+
+    #[test]
+    fn admin_dialogs_are_for_the_scrum_master() {
+        let mut app = app_at((1, 1));
+        app.update(Action::OpenStartRound);
+        app.update(Action::AskReset);
+        assert!(app.modal.is_none());
+        assert!(app.update(Action::Reveal).is_empty());
+
+        me_mut(&mut app).role = Role::ScrumMaster;
+        app.update(Action::OpenStartRound);
+        assert!(matches!(app.modal, Some(Modal::StartRound(_))));
+        assert!(matches!(app.update(Action::Reveal).as_slice(), [ClientPayload::Admin(AdminCommand::Reveal)]));
+    }
+
+    fn scrum_master() -> App {
+        let mut app = app_at((1, 1));
+        me_mut(&mut app).role = Role::ScrumMaster;
+        app
+    }
+
+    #[test]
+    fn the_card_picker_votes_for_the_highlighted_card() {
+        let mut app = app_at((1, 1));
+        app.update(Action::OpenCardPicker);
+        app.update(Action::MovePickerSelection { forward: false });
+        assert!(matches!(app.modal, Some(Modal::CardPicker { selected: 0 })));
+        for _ in 0..4 {
+            app.update(Action::MovePickerSelection { forward: true });
+        }
+
+        // Cards are 0, 1, 2, 3, 5, ...
+        let (x, y) = zone_center(&app, 5);
+        let out = app.update(Action::PickSelectedCard);
+        assert!(matches!(out[..], [ClientPayload::Move { x: mx, y: my }] if (mx, my) == (x, y)));
+        assert!(app.modal.is_none());
+    }
+
+    #[test]
+    fn the_start_round_form_sends_what_was_entered() {
+        let mut app = scrum_master();
+        app.update(Action::OpenStartRound);
+        for c in "Fix login".chars() {
+            app.update(Action::EditStartTitle(InputRequest::InsertChar(c)));
+        }
+        app.update(Action::FocusStartField { forward: true });
+        app.update(Action::CycleStartTimeout { forward: true });
+
+        let out = app.update(Action::SubmitStartRound);
+        assert!(app.modal.is_none());
+        let [ClientPayload::Admin(AdminCommand::StartVote { ticket, timeout })] = &out[..] else {
+            panic!("expected StartVote, got {:?}", out);
+        };
+        assert_eq!(ticket.as_ref().map(|t| t.title.as_str()), Some("Fix login"));
+        assert_eq!(*timeout, Some(30));
+    }
+
+    #[test]
+    fn a_round_can_start_from_the_backlog() {
+        let mut app = scrum_master();
+        app.game_state.as_mut().unwrap().backlog = vec![Ticket::new("PROJ-1"), Ticket::new("PROJ-2")];
+        app.update(Action::OpenStartRound);
+        app.update(Action::FocusStartField { forward: false });
+        app.update(Action::MoveBacklogSelection { down: true });
+        app.update(Action::MoveBacklogSelection { down: true });
+
+        let out = app.update(Action::SubmitStartRound);
+        assert!(matches!(&out[..], [ClientPayload::Admin(AdminCommand::StartVote { ticket: Some(t), .. })] if t.title == "PROJ-2"));
+    }
+
+    #[test]
+    fn reset_waits_for_confirmation() {
+        let mut app = scrum_master();
+        app.update(Action::AskReset);
+        assert!(app.update(Action::CloseModal).is_empty());
+        assert!(app.modal.is_none());
+
+        // Confirming only counts with the dialog open
+        assert!(app.update(Action::ConfirmReset).is_empty());
+        app.update(Action::AskReset);
+        assert!(matches!(app.update(Action::ConfirmReset)[..], [ClientPayload::Admin(AdminCommand::Reset)]));
+        assert!(app.modal.is_none());
+    }
+
+    #[test]
+    fn dialog_actions_do_nothing_without_their_dialog() {
+        let mut app = scrum_master();
+        app.update(Action::ShowPlayer(app.self_id.unwrap()));
+        for action in [
+            Action::PickSelectedCard,
+            Action::SubmitStartRound,
+            Action::EditStartTitle(InputRequest::InsertChar('x')),
+            Action::MovePickerSelection { forward: true },
+        ] {
+            assert!(app.update(action).is_empty());
+        }
+        assert!(matches!(app.modal, Some(Modal::PlayerDetails(_))));
+    }
+}
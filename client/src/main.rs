@@ -1,28 +1,35 @@
 use std::{io, time::Duration};
 use ratatui::{
     backend::CrosstermBackend,
-    widgets::{Block, Borders, Paragraph, Clear},
-    layout::{Layout, Constraint, Direction, Rect},
+    layout::Rect,
     Terminal,
-    style::{Style, Color, Modifier},
-    symbols,
-    text::{Line, Span},
 };
 use crossterm::{
-    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode},
+    event::{DisableMouseCapture, EnableMouseCapture, Event, EventStream, KeyCode, KeyEvent, MouseButton, MouseEvent, MouseEventKind},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
-use common::{ClientPayload, ServerPayload, Role};
-use tui_input::backend::crossterm::EventHandler;
+use common::{ClientPayload, ErrorCode, ServerPayload, Role};
+use futures::StreamExt;
+use tui_input::backend::crossterm::{to_input_request, EventHandler};
 
 mod app;
-mod network;
+mod mouse;
 mod ui; // We will implement UI in a separate file too, or keep it simple here? 
-mod zones;
 // Let's create ui.rs for the draw functions
-use app::{App, CurrentScreen};
-use network::Network;
+use app::{Action, App, CurrentScreen, Modal, StartField, DIGIT_WAIT};
+use sdk::Connection;
+
+// How often timers are checked; also the frame rate while a round's clock is running.
+const TICK: Duration = Duration::from_millis(250);
+
+// Where the server connection stands after a batch of messages.
+enum Link {
+    Open,
+    // Login refused; stay on the login screen with the reason shown
+    Rejected,
+    Lost(String),
+}
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -34,246 +41,68 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let mut terminal = Terminal::new(backend)?;
 
     let mut app = App::new();
-    let mut network: Option<Network> = None;
+    let mut network: Option<Connection> = None;
+    let mut events = EventStream::new();
+    let mut tick = tokio::time::interval(TICK);
+    tick.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+    // Only redraw when something changed
+    let mut dirty = true;
 
     loop {
-        terminal.draw(|f| ui::ui(f, &app))?;
-        
-        // Calculate dynamic boundaries based on terminal size
-        // Layout duplicate logic:
-        // Top status (3) + Bottom (5) ... wait, ui.rs constraints says:
-        /*
-            Constraint::Length(3), // Status
-            Constraint::Min(10), // Map
-            Constraint::Length(5), // Bottom
-        */
-        // Margin 2
-        
-        let size = terminal.size()?;
-        if size.width > 4 && size.height > 10 { // Minimal check
-             let available_width = size.width.saturating_sub(4); // Margin 2 either side
-             // Inner map also has margin 1 either side -> total -2
-             let map_inner_width = available_width.saturating_sub(2);
-             
-             let available_height = size.height.saturating_sub(4); // Margin 2 top/bottom
-             let map_height = available_height.saturating_sub(3 + 5); // Status + Bottom
-             // Inner map margin 1 top/bottom
-             let map_inner_height = map_height.saturating_sub(2);
-             
-             // Scale 3x2
-             app.grid_width = map_inner_width / 3;
-             app.grid_height = (map_inner_height as f32 * 0.9) as u16;
+        if dirty {
+            terminal.draw(|f| ui::ui(f, &app))?;
+            dirty = false;
         }
 
-        // Poll for events. NOTE: Blocking poll is tricky with async networking.
-        // We should use `crossterm::event::EventStream` or verify poll non-blocking.
-        // But `terminal.draw` is fast.
-        // We need to poll network AND inputs.
-        
-        // Handle Network
-        if let Some(net) = &mut network {
-            match net.rx.try_recv() {
-                Ok(msg) => {
-                     // Parse server message
-                     if let Ok(payload) = serde_json::from_str::<ServerPayload>(&msg) {
-                         match payload {
-                             ServerPayload::Welcome { self_id, state } => {
-                                 app.log("Connected to server".to_string());
-                                 app.self_id = Some(self_id);
-                                 app.game_state = Some(state);
-                                 app.current_screen = CurrentScreen::Main;
-                                 app.connection_error = None; // Clear error
-                             },
-                             ServerPayload::StateUpdate(state) => {
-                                 app.game_state = Some(state);
-                             },
-                             ServerPayload::Error(e) => {
-                                 app.log(format!("Server Error: {}", e));
-                             }
-                         }
-                     }
-                },
-                Err(tokio::sync::mpsc::error::TryRecvError::Empty) => {},
-                Err(tokio::sync::mpsc::error::TryRecvError::Disconnected) => {
-                    app.log("Disconnected from server".to_string());
-                    app.connection_error = Some("Disconnected from server".to_string());
-                    network = None;
-                    app.game_state = None;
-                    app.current_screen = CurrentScreen::Login;
+        tokio::select! {
+            event = events.next() => {
+                let Some(event) = event else { break };
+                let size = terminal.size()?;
+                if handle_event(&mut app, &mut network, Rect::new(0, 0, size.width, size.height), event?).await? {
+                    break;
                 }
+                dirty = true;
             }
-        }
-
-        // Handle Input - drain all pending events for responsive movement
-        let mut should_break = false;
-        while event::poll(Duration::from_millis(0))? {
-            if let Event::Key(key) = event::read()? {
-                match app.current_screen {
-                    CurrentScreen::Login => {
-                        match key.code {
-                            KeyCode::Enter => {
-                                // Connect
-                                match Network::connect(&app.server_url).await {
-                                    Ok(net) => {
-                                        // Validate Name
-                                        if app.name_input.value().trim().is_empty() {
-                                            app.log("Name cannot be empty!".to_string());
-                                        } else {
-                                            // Send Login
-                                            let login = ClientPayload::Login {
-                                                name: app.name_input.value().to_string(),
-                                                role: app.role_input.clone(),
-                                                color: app.color_input.clone(),
-                                                symbol: app.symbol_input.clone(),
-                                            };
-                                            app.log(format!("Logging in as {} ({:?})", app.name_input.value(), app.role_input));
-                                            let json = serde_json::to_string(&login)?;
-                                            net.tx.send(json)?;
-                                            network = Some(net);
-                                        }
-                                    },
-                                    Err(e) => {
-                                        app.connection_error = Some(format!("Connection Failed: {}", e));
-                                        app.log(format!("Connection Error: {}", e));
-                                    }
-                                }
-                            }
-                            KeyCode::Esc => {
-                                should_break = true;
-                                break;
-                            }
-                            KeyCode::Tab => {
-                                // Cycle role
-                                app.role_input = match app.role_input {
-                                    Role::Participant => Role::ScrumMaster,
-                                    Role::ScrumMaster => Role::Observer,
-                                    Role::Observer => Role::Participant,
-                                };
-                            }
-                            KeyCode::F(1) => {
-                                app.color_input = app.color_input.next();
-                            }
-                            KeyCode::F(2) => {
-                                app.symbol_input = app.symbol_input.next();
-                            }
-                            _ => {
-                                // Input name
-                                app.name_input.handle_event(&Event::Key(key));
-                            }
-                        }
-                    },
-                    CurrentScreen::Main => {
-                        // Main game inputs
-                         match key.code {
-                            KeyCode::Esc => { app.log("Quit".to_string()); should_break = true; break; },
-                            KeyCode::Char('q') => { app.log("Quit".to_string()); should_break = true; break; },
-                            KeyCode::Left => {
-                                if let Some(net) = &network {
-                                     let (x,y, confirmed) = app.game_state.as_ref().map(|s| {
-                                        s.players.get(&app.self_id.unwrap()).map(|p| (p.position.0, p.position.1, p.confirmed)).unwrap_or((10,10, false))
-                                    }).unwrap_or((10,10, false));
-                                    
-                                    if !confirmed && x > 0 { 
-                                        let new_x = x - 1;
-                                        let msg = ClientPayload::Move { x: new_x, y };
-                                        let _ = net.tx.send(serde_json::to_string(&msg)?);
-                                        check_zone_vote(new_x, y, &app.game_state, net);
-                                    }
-                                }
-                            }
-                            KeyCode::Right => {
-                                if let Some(net) = &network {
-                                     let (x,y, confirmed) = app.game_state.as_ref().map(|s| {
-                                        s.players.get(&app.self_id.unwrap()).map(|p| (p.position.0, p.position.1, p.confirmed)).unwrap_or((10,10, false))
-                                    }).unwrap_or((10,10, false));
-                                    
-                                    let new_x = x + 1;
-                                    if !confirmed && new_x < app.grid_width { // Dynamic Boundary
-                                        let msg = ClientPayload::Move { x: new_x, y };
-                                        let _ = net.tx.send(serde_json::to_string(&msg)?);
-                                        check_zone_vote(new_x, y, &app.game_state, net);
-                                    }
-                                }
-                            }
-                             KeyCode::Up => {
-                                if let Some(net) = &network {
-                                     let (x,y, confirmed) = app.game_state.as_ref().map(|s| {
-                                        s.players.get(&app.self_id.unwrap()).map(|p| (p.position.0, p.position.1, p.confirmed)).unwrap_or((10,10, false))
-                                    }).unwrap_or((10,10, false));
-                                    
-                                    if !confirmed && y > 0 { 
-                                        let new_y = y - 1;
-                                        let msg = ClientPayload::Move { x, y: new_y };
-                                        let _ = net.tx.send(serde_json::to_string(&msg)?);
-                                        check_zone_vote(x, new_y, &app.game_state, net);
-                                    }
-                                }
-                            }
-                             KeyCode::Down => {
-                                if let Some(net) = &network {
-                                     let (x,y, confirmed) = app.game_state.as_ref().map(|s| {
-                                        s.players.get(&app.self_id.unwrap()).map(|p| (p.position.0, p.position.1, p.confirmed)).unwrap_or((10,10, false))
-                                    }).unwrap_or((10,10, false));
-                                    
-                                    let new_y = y + 1;
-                                    if !confirmed && new_y < app.grid_height { // Dynamic Boundary
-                                        let msg = ClientPayload::Move { x, y: new_y };
-                                        let _ = net.tx.send(serde_json::to_string(&msg)?);
-                                        check_zone_vote(x, new_y, &app.game_state, net);
-                                    }
-                                }
-                            }
-                             // Vote Confirmation
-                             KeyCode::Char(' ') => {
-                                 if let Some(net) = &network {
-                                     let (x,y, confirmed) = app.game_state.as_ref().map(|s| {
-                                        s.players.get(&app.self_id.unwrap()).map(|p| (p.position.0, p.position.1, p.confirmed)).unwrap_or((10,10, false))
-                                    }).unwrap_or((10,10, false));
-                                    
-                                    let new_confirmed = !confirmed;
-                                    app.log(format!("Toggled confirmed: {}", new_confirmed));
-                                    let msg = ClientPayload::VoteConfirm { confirmed: new_confirmed };
-                                    let _ = net.tx.send(serde_json::to_string(&msg)?);
-                                    
-                                    // FORCE RESEND VOTE: ensure stationary players vote
-                                    check_zone_vote(x, y, &app.game_state, net);
-                                 }
-                             },
-                             // Voting hotkeys (removed)
-                             // Admin commands
-                            KeyCode::Char('s') => { // Start
-                                 app.log("Admin: Start Vote".to_string());
-                                 let cmd = common::AdminCommand::StartVote { ticket: None, timeout: Some(20) };
-                                 if let Some(net) = &network {
-                                     let _ = net.tx.send(serde_json::to_string(&ClientPayload::Admin(cmd))?);
-                                 }
-                            },
-                             KeyCode::Char('r') => { // Reveal
-                                 app.log("Admin: Reveal".to_string());
-                                 let cmd = common::AdminCommand::Reveal;
-                                 if let Some(net) = &network {
-                                     let _ = net.tx.send(serde_json::to_string(&ClientPayload::Admin(cmd))?);
-                                 }
-                            },
-                            //  KeyCode::Char('0') => { // Reset
-                            //      app.log("Admin: Reset".to_string());
-                            //      let cmd = common::AdminCommand::Reset;
-                            //      if let Some(net) = &network {
-                            //          net.tx.send(serde_json::to_string(&ClientPayload::Admin(cmd))?)?;
-                            //      }
-                            // },
-                            _ => {}
-                        }
+            message = next_message(&mut network) => {
+                let ticket_before = app.game_state.as_ref().and_then(|s| s.current_ticket.clone());
+                let link = match (message, network.as_mut()) {
+                    (Ok(payload), Some(net)) => drain_messages(&mut app, net, payload),
+                    (Err(e), _) => lost(e),
+                    (Ok(_), None) => lost(sdk::Error::Closed),
+                };
+                // A new ticket starts at the top of its panel
+                if app.game_state.as_ref().and_then(|s| s.current_ticket.as_ref()) != ticket_before.as_ref() {
+                    app.ticket_scroll = 0;
+                }
+                match link {
+                    Link::Open => {}
+                    Link::Rejected => network = None,
+                    Link::Lost(reason) => {
+                        network = None;
+                        back_to_login(&mut app, reason);
                     }
                 }
+                dirty = true;
+            }
+            _ = tick.tick() => {
+                if let Some(net) = network.as_mut() {
+                    if !net.heartbeat() {
+                        network = None;
+                        back_to_login(&mut app, "Server not responding".to_string());
+                        dirty = true;
+                    }
+                }
+                if app.typed_at.is_some_and(|at| at.elapsed() >= DIGIT_WAIT) {
+                    let outbound = app.update(Action::DigitsTimedOut);
+                    send_all(&network, outbound);
+                    dirty = true;
+                }
+                // The countdown and the zone glow move on their own during a round
+                if app.game_state.as_ref().is_some_and(|s| matches!(s.phase, common::Phase::Voting { .. })) {
+                    dirty = true;
+                }
             }
         }
-        if should_break {
-            break;
-        }
-        
-        // Small sleep to prevent busy-looping when no events
-        tokio::time::sleep(Duration::from_millis(16)).await;
     }
 
     // Cleanup
@@ -284,39 +113,273 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
-fn send_vote(network: &Option<Network>, val: Option<u32>) {
-     if let Some(net) = network {
-         let msg = ClientPayload::Vote { value: val };
-         let _ = net.tx.send(serde_json::to_string(&msg).unwrap());
-     }
+// The session token survives, so logging in again resumes the same player.
+fn back_to_login(app: &mut App, reason: String) {
+    app.log(reason.clone());
+    app.connection_error = Some(reason);
+    app.game_state = None;
+    app.current_screen = CurrentScreen::Login;
+}
+
+// Never resolves while disconnected, so `select!` just waits on the other branches.
+async fn next_message(network: &mut Option<Connection>) -> Result<ServerPayload, sdk::Error> {
+    match network {
+        Some(net) => net.recv().await,
+        None => std::future::pending().await,
+    }
+}
+
+// A plain hang-up needs no explanation; anything else says what went wrong.
+fn lost(e: sdk::Error) -> Link {
+    match e {
+        sdk::Error::Closed => Link::Lost("Disconnected from server".to_string()),
+        e => Link::Lost(format!("Disconnected from server: {}", e)),
+    }
+}
+
+// Handle `first` and everything else already queued, so a burst of updates
+// costs one redraw.
+fn drain_messages(app: &mut App, net: &mut Connection, first: ServerPayload) -> Link {
+    let mut next = Some(first);
+    while let Some(payload) = next {
+        let link = handle_server_message(app, net, payload);
+        if !matches!(link, Link::Open) {
+            return link;
+        }
+        next = match net.try_recv() {
+            Ok(next) => next,
+            Err(e) => return lost(e),
+        };
+    }
+    Link::Open
+}
+
+fn handle_server_message(app: &mut App, net: &Connection, payload: ServerPayload) -> Link {
+    match payload {
+        ServerPayload::Welcome { self_id, state, session_token, server } => {
+            app.log(format!("Connected to server v{}", server.version));
+            if server.protocol_version != common::PROTOCOL_VERSION {
+                app.log(format!(
+                    "Protocol mismatch: server speaks v{}, client v{}",
+                    server.protocol_version,
+                    common::PROTOCOL_VERSION
+                ));
+            }
+            app.server_info = Some(server);
+            app.self_id = Some(self_id);
+            app.session_token = Some(session_token);
+            app.game_state = Some(state);
+            app.current_screen = CurrentScreen::Main;
+            app.connection_error = None; // Clear error
+        },
+        ServerPayload::StateUpdate(state) => {
+            app.game_state = Some(state);
+        },
+        ServerPayload::Events(events) => {
+            // Replay deltas on our copy; if one doesn't line up, resync.
+            let in_sync = match app.game_state.as_mut() {
+                Some(state) => events.iter().all(|e| state.apply(e)),
+                None => false,
+            };
+            if !in_sync {
+                let _ = net.send(ClientPayload::RequestSnapshot);
+            }
+        },
+        ServerPayload::Pong { .. } => {},
+        ServerPayload::Shutdown { reason } => {
+            return Link::Lost(format!("{} - press ENTER to rejoin once it is back", reason));
+        },
+        // The server hangs up right after; a fresh login starts a new player
+        ServerPayload::Error { code: ErrorCode::Kicked, message } => {
+            app.session_token = None;
+            return Link::Lost(message);
+        }
+        ServerPayload::Error { code, message } => {
+            app.log(format!("Server Error ({:?}): {}", code, message));
+            // A rejected login leaves us on the login screen; show why
+            if let CurrentScreen::Login = app.current_screen {
+                app.connection_error = Some(message);
+                return Link::Rejected;
+            }
+        }
+    }
+    Link::Open
+}
+
+// Keyboard, mouse and resize events. Returns true when the user quits.
+async fn handle_event(
+    app: &mut App,
+    network: &mut Option<Connection>,
+    area: Rect,
+    event: Event,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    // Only the view changes; the caller redraws
+    if let Event::Resize(..) = event {
+        return Ok(false);
+    }
+    if let (Event::Mouse(mouse), CurrentScreen::Main) = (&event, &app.current_screen) {
+        let outbound = handle_mouse(app, *mouse, area);
+        send_all(network, outbound);
+        return Ok(false);
+    }
+    if let Event::Key(key) = event {
+        match app.current_screen {
+            CurrentScreen::Login => {
+                match key.code {
+                    KeyCode::Enter => {
+                        // Connect
+                        match Connection::connect(app.server_url.as_str()).await {
+                            Ok(net) => {
+                                // Validate Name
+                                if app.name_input.value().trim().is_empty() {
+                                    app.log("Name cannot be empty!".to_string());
+                                } else {
+                                    // Send Login
+                                    let login = ClientPayload::Login {
+                                        name: app.name_input.value().to_string(),
+                                        role: app.role_input.clone(),
+                                        color: app.color_input.clone(),
+                                        symbol: app.symbol_input.clone(),
+                                        resume_token: app.session_token,
+                                    };
+                                    app.log(format!("Logging in as {} ({:?})", app.name_input.value(), app.role_input));
+                                    net.send(login)?;
+                                    *network = Some(net);
+                                }
+                            },
+                            Err(e) => {
+                                app.connection_error = Some(format!("Connection Failed: {}", e));
+                                app.log(format!("Connection Error: {}", e));
+                            }
+                        }
+                    }
+                    KeyCode::Esc => {
+                        return Ok(true);
+                    }
+                    KeyCode::Tab => {
+                        // Cycle role
+                        app.role_input = match app.role_input {
+                            Role::Participant => Role::ScrumMaster,
+                            Role::ScrumMaster => Role::Observer,
+                            Role::Observer => Role::Participant,
+                        };
+                    }
+                    KeyCode::F(1) => {
+                        app.color_input = app.color_input.next();
+                    }
+                    KeyCode::F(2) => {
+                        app.symbol_input = app.symbol_input.next();
+                    }
+                    _ => {
+                        // Input name
+                        app.name_input.handle_event(&Event::Key(key));
+                    }
+                }
+            },
+            CurrentScreen::Main if app.modal.is_some() => {
+                if let Some(action) = modal_action(app, key) {
+                    let outbound = app.update(action);
+                    send_all(network, outbound);
+                }
+            },
+            CurrentScreen::Main => {
+                if matches!(key.code, KeyCode::Esc | KeyCode::Char('q')) {
+                    app.log("Quit".to_string());
+                    return Ok(true);
+                }
+                if let Some(action) = main_action(app, key, area) {
+                    let outbound = app.update(action);
+                    send_all(network, outbound);
+                }
+            }
+        }
+    }
+    Ok(false)
+}
+
+// Keys on the main screen, with no dialog open.
+fn main_action(app: &App, key: KeyEvent, area: Rect) -> Option<Action> {
+    let action = match key.code {
+        KeyCode::Left => Action::Step { dx: -1, dy: 0 },
+        KeyCode::Right => Action::Step { dx: 1, dy: 0 },
+        KeyCode::Up => Action::Step { dx: 0, dy: -1 },
+        KeyCode::Down => Action::Step { dx: 0, dy: 1 },
+        KeyCode::Char(' ') => Action::ToggleConfirm,
+        KeyCode::PageUp => Action::ScrollTicket { down: false, max: 0 },
+        KeyCode::PageDown => {
+            let max = app.game_state.as_ref()
+                .and_then(|s| s.current_ticket.as_ref())
+                .map(|t| ui::ticket_max_scroll(t, area.height))
+                .unwrap_or(0);
+            Action::ScrollTicket { down: true, max }
+        }
+        // Keyboard voting: type a card's value, or pick it from a list
+        KeyCode::Char(c) if c.is_ascii_digit() => Action::TypeDigit(c),
+        KeyCode::Enter => Action::SubmitDigits,
+        KeyCode::Char('v') => Action::OpenCardPicker,
+        // Admin commands
+        KeyCode::Char('s') => Action::OpenStartRound,
+        KeyCode::Char('r') => Action::Reveal,
+        KeyCode::Char('x') => Action::AskReset,
+        _ => return None,
+    };
+    Some(action)
+}
+
+// Keys while a dialog is open; they never reach the room behind it.
+fn modal_action(app: &App, key: KeyEvent) -> Option<Action> {
+    let action = match app.modal.as_ref()? {
+        // Any key closes the player card
+        Modal::PlayerDetails(_) => Action::CloseModal,
+        Modal::ConfirmReset => match key.code {
+            KeyCode::Char('y') | KeyCode::Char('Y') | KeyCode::Enter => Action::ConfirmReset,
+            _ => Action::CloseModal,
+        },
+        Modal::CardPicker { .. } => match key.code {
+            KeyCode::Left | KeyCode::Up => Action::MovePickerSelection { forward: false },
+            KeyCode::Right | KeyCode::Down => Action::MovePickerSelection { forward: true },
+            KeyCode::Enter | KeyCode::Char(' ') => Action::PickSelectedCard,
+            KeyCode::Esc | KeyCode::Char('v') => Action::CloseModal,
+            _ => return None,
+        },
+        Modal::StartRound(form) => match (key.code, form.focus) {
+            (KeyCode::Esc, _) => Action::CloseModal,
+            (KeyCode::Enter, _) => Action::SubmitStartRound,
+            (KeyCode::Tab, _) => Action::FocusStartField { forward: true },
+            (KeyCode::BackTab, _) => Action::FocusStartField { forward: false },
+            (KeyCode::Left, StartField::Timeout) => Action::CycleStartTimeout { forward: false },
+            (KeyCode::Right | KeyCode::Char(' '), StartField::Timeout) => Action::CycleStartTimeout { forward: true },
+            (KeyCode::Up, StartField::Backlog) => Action::MoveBacklogSelection { down: false },
+            (KeyCode::Down, StartField::Backlog) => Action::MoveBacklogSelection { down: true },
+            (_, StartField::Title) => Action::EditStartTitle(to_input_request(&Event::Key(key))?),
+            _ => return None,
+        },
+    };
+    Some(action)
+}
+
+fn handle_mouse(app: &mut App, mouse: MouseEvent, area: Rect) -> Vec<ClientPayload> {
+    if app.modal.is_some() {
+        // Any click dismisses the player card; the other dialogs are keyboard-only
+        if matches!(app.modal, Some(Modal::PlayerDetails(_))) && matches!(mouse.kind, MouseEventKind::Down(_)) {
+            return app.update(Action::CloseModal);
+        }
+        return Vec::new();
+    }
+    let action = match (mouse.kind, mouse::hit_test(app, area, mouse.column, mouse.row)) {
+        (MouseEventKind::Down(MouseButton::Left), mouse::Target::MapCell(x, y)) => Action::MoveTo(x, y),
+        (MouseEventKind::Down(MouseButton::Left), mouse::Target::Player(id)) => Action::ShowPlayer(id),
+        (MouseEventKind::ScrollUp, mouse::Target::Log) => Action::ScrollLog { up: true },
+        (MouseEventKind::ScrollDown, mouse::Target::Log) => Action::ScrollLog { up: false },
+        _ => return Vec::new(),
+    };
+    app.update(action)
 }
 
-fn check_zone_vote(x: u16, y: u16, state: &Option<common::GameState>, net: &Network) {
-    if let Some(s) = state {
-        let zones = crate::zones::calculate_zones(&s.config);
-        for zone in zones {
-             if x >= zone.x && x < zone.x + zone.width && y >= zone.y && y < zone.y + zone.height {
-                 // In zone, vote!
-                 let msg = ClientPayload::Vote { value: Some(zone.value) };
-                 let _ = net.tx.send(serde_json::to_string(&msg).unwrap());
-                 return;
-             }
+fn send_all(network: &Option<Connection>, outbound: Vec<ClientPayload>) {
+    if let Some(net) = network {
+        for msg in outbound {
+            let _ = net.send(msg);
         }
-        // If not in any zone, maybe unvote? 
-        // "Reset the state... pull everyone back".
-        // Let's imply leaving zone = keep vote? Or unvote?
-        // Prompt: "can vote... change their vote".
-        // Usually spatial means if I leave, I haven't voted.
-        // But let's stick to explicit vote for now to be safe, or unvote if outside?
-        // Let's Unvote if outside all zones?
-        // "until every participant has answered".
-        // If I walk out, did I withdraw my answer?
-        // Let's implement unvote if outside all zones.
-        
-        // Check if we WERE in a zone (to avoid spamming unvote).
-        // Too complex for stateless check.
-        // Just send Unvote (None) if not in zone.
-        let msg = ClientPayload::Vote { value: None };
-        let _ = net.tx.send(serde_json::to_string(&msg).unwrap());
     }
 }
@@ -0,0 +1,44 @@
+use ratatui::layout::{Position, Rect};
+use uuid::Uuid;
+
+use crate::app::App;
+use crate::ui::{main_layout, sorted_players, too_small};
+
+// What a click or scroll on the main screen landed on.
+#[derive(Debug, PartialEq)]
+pub enum Target {
+    // Grid cell on the map, in the same units as `Player::position`
+    MapCell(u16, u16),
+    Player(Uuid),
+    Log,
+    Nothing,
+}
+
+// `area` is the whole terminal, as passed to `ui::ui`.
+pub fn hit_test(app: &App, area: Rect, column: u16, row: u16) -> Target {
+    let Some(state) = &app.game_state else {
+        return Target::Nothing;
+    };
+    if too_small(area, app) {
+        return Target::Nothing;
+    }
+    let layout = main_layout(area, app);
+    let at = Position { x: column, y: row };
+
+    if layout.map_inner.contains(at) {
+        // ui.rs draws each grid cell two columns wide and one row high
+        return Target::MapCell((column - layout.map_inner.x) / 2, row - layout.map_inner.y);
+    }
+    let players = layout.players.inner(ratatui::layout::Margin { vertical: 1, horizontal: 1 });
+    if players.contains(at) {
+        let index = (row - players.y) as usize;
+        return match sorted_players(state).get(index) {
+            Some(player) => Target::Player(player.id),
+            None => Target::Nothing,
+        };
+    }
+    if layout.log.contains(at) {
+        return Target::Log;
+    }
+    Target::Nothing
+}
@@ -1,15 +1,28 @@
 use ratatui::{
     Frame,
     layout::{Constraint, Direction, Layout, Rect, Alignment},
-    widgets::{Block, Borders, Paragraph},
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
     style::{Color, Style, Modifier},
     text::{Line, Span},
 };
-use crate::app::{App, CurrentScreen};
-use common::Phase;
+use crate::app::{App, CurrentScreen, Modal, StartField, StartRoundForm};
+use common::{Phase, Presence, Ticket};
 // use uuid::Uuid; // Unused
 
+// The ticket panel sits right of the map, on terminals wide enough to spare it.
+pub const TICKET_PANEL_WIDTH: u16 = 40;
+// Status bar and bottom section heights, see `draw_main`
+const STATUS_HEIGHT: u16 = 3;
+const BOTTOM_HEIGHT: u16 = 12;
+// Smallest terminal for the login form; the room needs enough for its whole map
+pub const MIN_TERMINAL_WIDTH: u16 = 80;
+pub const MIN_TERMINAL_HEIGHT: u16 = 30;
+
 pub fn ui(f: &mut Frame, app: &App) {
+    if too_small(f.area(), app) {
+        draw_too_small(f, app);
+        return;
+    }
     match app.current_screen {
         CurrentScreen::Login => draw_login(f, app),
         CurrentScreen::Main => draw_main(f, app),
@@ -32,7 +45,7 @@ fn draw_login(f: &mut Frame, app: &App) {
             ]
             .as_ref(),
         )
-        .split(f.size());
+        .split(f.area());
 
     let title = Paragraph::new("Poker Planning CLI")
         .style(Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD))
@@ -75,16 +88,107 @@ fn draw_login(f: &mut Frame, app: &App) {
     }
 }
 
+// Where each panel of the main screen goes. Drawing and mouse hit-testing
+// both use this so they can't disagree.
+pub struct MainLayout {
+    pub status: Rect,
+    pub map: Rect,
+    // Inside the map border; grid cell (x, y) is at column x*2, row y from here
+    pub map_inner: Rect,
+    pub ticket: Option<Rect>,
+    pub players: Rect,
+    pub info: Rect,
+    pub log: Rect,
+    pub help: Rect,
+}
+
+pub fn main_layout(area: Rect, app: &App) -> MainLayout {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(STATUS_HEIGHT),  // Top Bar (Status)
+            Constraint::Min(10),    // Middle (Map + Ticket)
+            Constraint::Length(BOTTOM_HEIGHT), // Bottom (Players + Info + Log + Help)
+        ].as_ref())
+        .split(area);
+
+    let (map, ticket) = if ticket_panel_visible(app, area.width) {
+        let middle = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Min(0), Constraint::Length(TICKET_PANEL_WIDTH)].as_ref())
+            .split(chunks[1]);
+        (middle[0], Some(middle[1]))
+    } else {
+        (chunks[1], None)
+    };
+
+    let bottom = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage(25), // Connected Players
+            Constraint::Percentage(20), // Info
+            Constraint::Percentage(30), // Log
+            Constraint::Percentage(25), // Help
+        ].as_ref())
+        .split(chunks[2]);
+
+    MainLayout {
+        status: chunks[0],
+        map,
+        map_inner: map.inner(ratatui::layout::Margin { vertical: 1, horizontal: 1 }),
+        ticket,
+        players: bottom[0],
+        info: bottom[1],
+        log: bottom[2],
+        help: bottom[3],
+    }
+}
+
+// Columns taken by the map panel when every cell is on screen: two per cell
+// plus the border.
+fn map_panel_width(map: &common::MapLayout) -> u16 {
+    map.width * 2 + 2
+}
+
+// The room is never squeezed, so every zone stays reachable: the terminal has
+// to fit the whole map with the status bar above it and the panels below.
+pub fn min_terminal_size(app: &App) -> (u16, u16) {
+    match (&app.current_screen, &app.game_state) {
+        (CurrentScreen::Main, Some(state)) => (
+            map_panel_width(&state.map).max(MIN_TERMINAL_WIDTH),
+            (state.map.height + 2 + STATUS_HEIGHT + BOTTOM_HEIGHT).max(MIN_TERMINAL_HEIGHT),
+        ),
+        _ => (MIN_TERMINAL_WIDTH, MIN_TERMINAL_HEIGHT),
+    }
+}
+
+pub fn too_small(area: Rect, app: &App) -> bool {
+    let (width, height) = min_terminal_size(app);
+    area.width < width || area.height < height
+}
+
+fn draw_too_small(f: &mut Frame, app: &App) {
+    let area = f.area();
+    let (width, height) = min_terminal_size(app);
+    let text = format!(
+        "Terminal too small\n{}x{}, needs at least {}x{}\nResize the window, or press Esc to quit",
+        area.width, area.height, width, height
+    );
+    let y = area.y + area.height.saturating_sub(3) / 2;
+    let rect = Rect { y, height: area.height.min(3), ..area };
+    f.render_widget(Paragraph::new(text).alignment(Alignment::Center).style(Style::default().fg(Color::Yellow)), rect);
+}
+
+// Players as listed in the Connected Players panel, one per row.
+pub fn sorted_players(state: &common::GameState) -> Vec<&common::Player> {
+    let mut players: Vec<_> = state.players.values().collect();
+    players.sort_by_key(|p| p.name.to_lowercase());
+    players
+}
+
 fn draw_main(f: &mut Frame, app: &App) {
     if let Some(state) = &app.game_state {
-        let chunks = Layout::default()
-            .direction(Direction::Vertical)
-            .constraints([
-                Constraint::Length(3),  // Top Bar (Status)
-                Constraint::Min(10),    // Middle (Map - Full Width)
-                Constraint::Length(12), // Bottom (Players + Help)
-            ].as_ref())
-            .split(f.area());
+        let layout = main_layout(f.area(), app);
             
         // 1. Status Bar
         let phase_str = match state.phase {
@@ -92,37 +196,43 @@ fn draw_main(f: &mut Frame, app: &App) {
             Phase::Voting { .. } => "VOTING",
             Phase::Revealed => "REVEALED",
         };
+        let server_version = app.server_info.as_ref()
+            .map(|info| format!(" | Server: v{}", info.version))
+            .unwrap_or_default();
         let status_text = format!(
-            "Phase: {} | Ticket: {} | Players: {}", 
+            "Phase: {} | Ticket: {} | Players: {}{}", 
             phase_str, 
-            state.current_ticket.as_ref().map(|t| t.title.clone()).unwrap_or("None".to_string()),
-            state.players.len()
+            state.current_ticket.as_ref().map(Ticket::display_name).unwrap_or("None".to_string()),
+            state.players.len(),
+            server_version
         );
         let status_bar = Paragraph::new(status_text)
             .block(Block::default().borders(Borders::ALL).title("Status"))
             .style(Style::default().fg(Color::Green));
-        f.render_widget(status_bar, chunks[0]);
+        f.render_widget(status_bar, layout.status);
 
-        // 2. Middle Area: Map (Full Width)
-        let map_rect = chunks[1];
+        // 2. Middle Area: Map, plus the ticket panel when there is room
+        if let (Some(area), Some(ticket)) = (layout.ticket, &state.current_ticket) {
+            draw_ticket(f, area, ticket, app.ticket_scroll);
+        }
+        let map_rect = layout.map;
         let map_block = Block::default().borders(Borders::ALL).title("Room");
         f.render_widget(map_block, map_rect);
         
-        let inner_rect = map_rect.inner(ratatui::layout::Margin { vertical: 1, horizontal: 1 });
+        let inner_rect = layout.map_inner;
         
         // Render Zones (Simplified loop based on previous reads)
         // Calculate pulse for unconfirmed zones
         // SystemTime might be jittery, frame-based is better if we had it in App, but simple time works.
         let timestamp = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_millis();
-        let pulse = (timestamp / 500) % 2 == 0; // Toggle every 500ms
+        let pulse = (timestamp / 500).is_multiple_of(2); // Toggle every 500ms
         
-        let zones = crate::zones::calculate_zones(&state.config);
-        for zone in zones {
+        for zone in &state.map.zones {
              let zone_rect = Rect {
                  x: inner_rect.x + (zone.x * 2), // Scale x 2
-                 y: inner_rect.y + (zone.y * 1), // Scale y 1
+                 y: inner_rect.y + zone.y, // Scale y 1
                  width: zone.width * 2, 
-                 height: zone.height * 1, 
+                 height: zone.height,
              };
              
              if zone_rect.right() <= inner_rect.right() && zone_rect.bottom() <= inner_rect.bottom() {
@@ -132,11 +242,10 @@ fn draw_main(f: &mut Frame, app: &App) {
                  let mut is_confirmed = false;
                  
                  if let Some(p) = self_player {
-                     if p.position.0 >= zone.x && p.position.0 < zone.x + zone.width &&
-                        p.position.1 >= zone.y && p.position.1 < zone.y + zone.height {
-                            is_in_zone = true;
-                            is_confirmed = p.confirmed;
-                        }
+                     if zone.contains(p.position.0, p.position.1) {
+                         is_in_zone = true;
+                         is_confirmed = p.confirmed;
+                     }
                  }
                  
                  let border_style = if is_in_zone {
@@ -236,7 +345,7 @@ fn draw_main(f: &mut Frame, app: &App) {
                      .alignment(Alignment::Center);
                  
                  // Explicitly check for self
-                 let is_me = app.self_id.map_or(false, |id| id == player.id);
+                 let is_me = app.self_id == Some(player.id);
                  let can_see = match state.phase {
                      Phase::Voting { .. } => is_me,
                      _ => true,
@@ -254,19 +363,9 @@ fn draw_main(f: &mut Frame, app: &App) {
              }
         }
         
-        // 3. Bottom Section (Players + Help + Info)
-        let bottom_chunks = Layout::default()
-             .direction(Direction::Horizontal)
-             .constraints([
-                 Constraint::Percentage(33), // Connected Players (Left)
-                 Constraint::Percentage(33), // Help (Middle)
-                 Constraint::Percentage(34), // Info (Right)
-             ].as_ref())
-             .split(chunks[2]);
-
+        // 3. Bottom Section (Players + Info + Log + Help)
         // Column 1: Connected Players (Sorted)
-        let mut sorted_players: Vec<_> = state.players.values().collect();
-        sorted_players.sort_by_key(|p| p.name.to_lowercase());
+        let sorted_players = sorted_players(state);
 
         let mut player_lines = Vec::new();
         for p in sorted_players {
@@ -316,20 +415,26 @@ fn draw_main(f: &mut Frame, app: &App) {
                     common::AvatarColor::White => Color::White,
              };
 
-             let name_style = if p.confirmed {
-                 Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)
-             } else {
-                 Style::default()
+             let name_style = match p.presence {
+                 Presence::Disconnected => Style::default().fg(Color::DarkGray).add_modifier(Modifier::CROSSED_OUT),
+                 _ if p.confirmed => Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD),
+                 Presence::Idle => Style::default().fg(Color::DarkGray),
+                 Presence::Active => Style::default(),
+             };
+             let presence_tag = match p.presence {
+                 Presence::Active => "",
+                 Presence::Idle => " [idle]",
+                 Presence::Disconnected => " [disconnected]",
              };
 
             player_lines.push(Line::from(vec![
                 Span::styled(format!("{} ", p_symbol), Style::default().fg(p_color)),
-                Span::styled(format!("{} ({:?})", p.name, p.role), name_style)
+                Span::styled(format!("{} ({:?}){}", p.name, p.role, presence_tag), name_style)
             ]));
         }
         let player_list = Paragraph::new(player_lines)
            .block(Block::default().borders(Borders::ALL).title("Connected Players"));
-        f.render_widget(player_list, bottom_chunks[0]);
+        f.render_widget(player_list, layout.players);
         
         // Column 3: Info (Phase & Stats)
         let help_text_bottom = match state.phase {
@@ -362,27 +467,470 @@ fn draw_main(f: &mut Frame, app: &App) {
               } else {
                   stats_text = "\n\nNo confirmed votes.".to_string();
               }
+              if let Some(estimate) = state.final_estimate {
+                  stats_text.push_str(&format!("\nFinal estimate: {}", estimate));
+              }
          }
+        if !app.typed_digits.is_empty() {
+            stats_text.push_str(&format!("\n\nCard: {}_", app.typed_digits));
+        }
         let info_content = format!("{}{}", help_text_bottom, stats_text);
         
         let info_block = Paragraph::new(info_content)
             .block(Block::default().borders(Borders::ALL).title("Info"));
-        f.render_widget(info_block, bottom_chunks[1]);
+        f.render_widget(info_block, layout.info);
+
+        // Column 3: Log, newest at the bottom; the scroll wheel pages back
+        let log_rows = layout.log.height.saturating_sub(2) as usize;
+        let end = app.logs.len().saturating_sub(app.log_scroll);
+        let log_lines: Vec<Line> = app.logs[end.saturating_sub(log_rows)..end]
+            .iter()
+            .map(|l| Line::from(l.as_str()))
+            .collect();
+        let log_title = if app.log_scroll > 0 { format!("Log (-{})", app.log_scroll) } else { "Log".to_string() };
+        let log_block = Paragraph::new(log_lines)
+            .block(Block::default().borders(Borders::ALL).title(log_title))
+            .style(Style::default().fg(Color::Gray));
+        f.render_widget(log_block, layout.log);
 
         // Column 2: Help (Controls)
         let help_lines = vec![
             Line::from(Span::styled("Controls:", Style::default().add_modifier(Modifier::BOLD))),
             Line::from("Arrows: Move"),
-            Line::from("Space: Confirm Vote"),
-            Line::from("R: Reveal (ScrumMaster)"),
-            Line::from("S: Start (ScrumMaster)"),
             Line::from("Space: Confirm/Unconfirm"),
+            Line::from("0-9: Vote by value, V: Pick a card"),
+            Line::from("Click: Move there / player info"),
+            Line::from("R: Reveal (ScrumMaster)"),
+            Line::from("S: Start round (ScrumMaster)"),
+            Line::from("X: Reset (ScrumMaster)"),
+            Line::from("PgUp/PgDn: Scroll ticket"),
             Line::from("Q: Quit"),
         ];
 
          let help_block = Paragraph::new(help_lines)
            .block(Block::default().borders(Borders::ALL).title("Help"))
            .style(Style::default().fg(Color::Gray));
-        f.render_widget(help_block, bottom_chunks[2]);
+        f.render_widget(help_block, layout.help);
+
+        match &app.modal {
+            Some(Modal::StartRound(form)) => draw_start_round(f, form, &state.backlog),
+            Some(Modal::ConfirmReset) => draw_confirm_reset(f),
+            Some(Modal::CardPicker { selected }) => draw_card_picker(f, &state.config.cards, *selected),
+            Some(Modal::PlayerDetails(id)) => {
+                if let Some(player) = state.players.get(id) {
+                    draw_player_details(f, app, state, player);
+                }
+            }
+            None => {}
+        }
+    }
+}
+
+// A `width` x `height` box in the middle of `area`, shrunk to fit.
+fn centered(area: Rect, width: u16, height: u16) -> Rect {
+    let width = width.min(area.width);
+    let height = height.min(area.height);
+    Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    }
+}
+
+fn focus_style(focused: bool) -> Style {
+    if focused {
+        Style::default().fg(Color::Yellow)
+    } else {
+        Style::default().fg(Color::DarkGray)
+    }
+}
+
+// Backlog rows shown in the start-round dialog before it scrolls.
+const BACKLOG_ROWS: u16 = 8;
+
+fn draw_start_round(f: &mut Frame, form: &StartRoundForm, backlog: &[Ticket]) {
+    let list_height = if backlog.is_empty() { 3 } else { (backlog.len() as u16).min(BACKLOG_ROWS) + 2 };
+    let area = centered(f.area(), 60, 3 + 3 + list_height + 1 + 2);
+    f.render_widget(Clear, area);
+    f.render_widget(Block::default().borders(Borders::ALL).title("Start round"), area);
+
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(1)
+        .constraints([
+            Constraint::Length(3),           // Ticket title
+            Constraint::Length(3),           // Timer
+            Constraint::Length(list_height), // Backlog
+            Constraint::Length(1),           // Keys
+        ].as_ref())
+        .split(area);
+
+    let title_focused = form.focus == StartField::Title;
+    let title = Paragraph::new(form.title.value())
+        .block(Block::default().borders(Borders::ALL).title("Ticket (optional)").border_style(focus_style(title_focused)));
+    f.render_widget(title, rows[0]);
+    if title_focused {
+        f.set_cursor_position((rows[0].x + 1 + form.title.visual_cursor() as u16, rows[0].y + 1));
+    }
+
+    let timer = match form.timeout {
+        Some(secs) => format!("< {}s >", secs),
+        None => "< no timer >".to_string(),
+    };
+    let timer = Paragraph::new(timer)
+        .block(Block::default().borders(Borders::ALL).title("Timer (Left/Right)").border_style(focus_style(form.focus == StartField::Timeout)));
+    f.render_widget(timer, rows[1]);
+
+    let backlog_focused = form.focus == StartField::Backlog;
+    let lines: Vec<Line> = if backlog.is_empty() {
+        vec![Line::from(Span::styled("Backlog is empty", Style::default().fg(Color::DarkGray)))]
+    } else {
+        // Keep the highlighted entry in view
+        let first = (form.backlog_selected + 1).saturating_sub(BACKLOG_ROWS as usize);
+        backlog
+            .iter()
+            .enumerate()
+            .skip(first)
+            .take(BACKLOG_ROWS as usize)
+            .map(|(i, ticket)| {
+                let selected = backlog_focused && i == form.backlog_selected;
+                let style = if selected {
+                    Style::default().fg(Color::Black).bg(Color::Yellow)
+                } else {
+                    Style::default()
+                };
+                Line::from(Span::styled(ticket.display_name(), style))
+            })
+            .collect()
+    };
+    let list = Paragraph::new(lines)
+        .block(Block::default().borders(Borders::ALL).title("Pick from backlog (Up/Down)").border_style(focus_style(backlog_focused)));
+    f.render_widget(list, rows[2]);
+
+    let keys = Paragraph::new("Tab: next field  Enter: start  Esc: cancel").style(Style::default().fg(Color::Gray));
+    f.render_widget(keys, rows[3]);
+}
+
+fn draw_card_picker(f: &mut Frame, cards: &[u32], selected: usize) {
+    let spans: Vec<Span> = cards
+        .iter()
+        .enumerate()
+        .map(|(i, card)| {
+            let style = if i == selected {
+                Style::default().fg(Color::Black).bg(Color::Yellow).add_modifier(Modifier::BOLD)
+            } else {
+                Style::default()
+            };
+            Span::styled(format!(" {} ", card), style)
+        })
+        .collect();
+    let area = centered(f.area(), 50, 6);
+    f.render_widget(Clear, area);
+    let picker = Paragraph::new(vec![
+        Line::from(spans),
+        Line::from(""),
+        Line::from(Span::styled("Left/Right: choose  Enter: vote  Esc: cancel", Style::default().fg(Color::Gray))),
+    ])
+    .alignment(Alignment::Center)
+    .wrap(Wrap { trim: false })
+    .block(Block::default().borders(Borders::ALL).title("Pick a card"));
+    f.render_widget(picker, area);
+}
+
+fn draw_player_details(f: &mut Frame, app: &App, state: &common::GameState, player: &common::Player) {
+    let vote = state.votes.get(&player.id).copied().flatten();
+    // The server withholds other players' votes and positions until the reveal
+    let hidden = matches!(state.phase, Phase::Voting { .. }) && app.self_id != Some(player.id);
+    let vote_text = match vote {
+        _ if hidden => "hidden until the reveal".to_string(),
+        None => "not voted".to_string(),
+        Some(v) => v.to_string(),
+    };
+    let position_text = if hidden {
+        "hidden until the reveal".to_string()
+    } else {
+        format!("{}, {}", player.position.0, player.position.1)
+    };
+    let lines = vec![
+        Line::from(Span::styled(player.name.as_str(), Style::default().add_modifier(Modifier::BOLD))),
+        Line::from(format!("Role: {:?}", player.role)),
+        Line::from(format!("Avatar: {:?} {:?}", player.color, player.symbol)),
+        Line::from(format!("Presence: {:?}", player.presence)),
+        Line::from(format!("Vote: {}{}", vote_text, if player.confirmed { " (confirmed)" } else { "" })),
+        Line::from(format!("Position: {}", position_text)),
+    ];
+    let area = centered(f.area(), 40, lines.len() as u16 + 2);
+    f.render_widget(Clear, area);
+    let card = Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title("Player"));
+    f.render_widget(card, area);
+}
+
+fn draw_confirm_reset(f: &mut Frame) {
+    let area = centered(f.area(), 44, 5);
+    f.render_widget(Clear, area);
+    let prompt = Paragraph::new(vec![
+        Line::from("Clear the votes and ticket for everyone?"),
+        Line::from(""),
+        Line::from(Span::styled("Y: reset   any other key: cancel", Style::default().fg(Color::Gray))),
+    ])
+    .alignment(Alignment::Center)
+    .block(Block::default().borders(Borders::ALL).title("Reset round").border_style(Style::default().fg(Color::Red)));
+    f.render_widget(prompt, area);
+}
+
+// Only beside the whole map; narrower terminals show the ticket in the status bar alone.
+pub fn ticket_panel_visible(app: &App, terminal_width: u16) -> bool {
+    app.game_state.as_ref().is_some_and(|s| {
+        s.current_ticket.is_some() && terminal_width >= map_panel_width(&s.map) + TICKET_PANEL_WIDTH
+    })
+}
+
+fn ticket_lines(ticket: &Ticket) -> Vec<Line<'_>> {
+    let mut lines = vec![Line::from(Span::styled(
+        ticket.title.as_str(),
+        Style::default().add_modifier(Modifier::BOLD),
+    ))];
+    let origin = match (&ticket.key, ticket.source) {
+        (Some(key), Some(source)) => Some(format!("{} ({:?})", key, source)),
+        (Some(key), None) => Some(key.clone()),
+        (None, Some(source)) => Some(format!("{:?}", source)),
+        (None, None) => None,
+    };
+    if let Some(origin) = origin {
+        lines.push(Line::from(Span::styled(origin, Style::default().fg(Color::DarkGray))));
+    }
+    if let Some(url) = &ticket.url {
+        lines.push(Line::from(Span::styled(
+            url.as_str(),
+            Style::default().fg(Color::Blue).add_modifier(Modifier::UNDERLINED),
+        )));
+    }
+    if !ticket.labels.is_empty() {
+        let labels: Vec<Span> = ticket
+            .labels
+            .iter()
+            .map(|l| Span::styled(format!("[{}] ", l), Style::default().fg(Color::Magenta)))
+            .collect();
+        lines.push(Line::from(labels));
+    }
+    lines.push(Line::from(""));
+    match &ticket.description {
+        Some(description) => lines.extend(description.lines().map(Line::from)),
+        None => lines.push(Line::from(Span::styled("No description.", Style::default().fg(Color::DarkGray)))),
+    }
+    lines
+}
+
+// Rows the ticket's lines take once wrapped to `width` columns.
+fn wrapped_height(lines: &[Line], width: u16) -> u16 {
+    let width = width.max(1) as usize;
+    lines
+        .iter()
+        .map(|line| line.width().max(1).div_ceil(width) as u16)
+        .sum()
+}
+
+// How far the ticket panel can scroll before the last line leaves the bottom.
+pub fn ticket_max_scroll(ticket: &Ticket, terminal_height: u16) -> u16 {
+    let inner_width = TICKET_PANEL_WIDTH - 2;
+    let inner_height = terminal_height.saturating_sub(STATUS_HEIGHT + BOTTOM_HEIGHT + 2);
+    wrapped_height(&ticket_lines(ticket), inner_width).saturating_sub(inner_height)
+}
+
+fn draw_ticket(f: &mut Frame, area: Rect, ticket: &Ticket, scroll: u16) {
+    let lines = ticket_lines(ticket);
+    let inner_height = area.height.saturating_sub(2);
+    let overflow = wrapped_height(&lines, area.width.saturating_sub(2)) > inner_height;
+    let title = if overflow { "Ticket (PgUp/PgDn)" } else { "Ticket" };
+    let panel = Paragraph::new(lines)
+        .wrap(Wrap { trim: false })
+        .scroll((scroll, 0))
+        .block(Block::default().borders(Borders::ALL).title(title));
+    f.render_widget(panel, area);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::{AvatarColor, AvatarSymbol, GameState, MapLayout, Player, Role, VotingConfig};
+    use ratatui::{backend::TestBackend, Terminal};
+    use std::collections::HashMap;
+    use std::path::PathBuf;
+    use uuid::Uuid;
+
+    const ALICE: Uuid = Uuid::from_u128(1);
+    const BOB: Uuid = Uuid::from_u128(2);
+    const CAROL: Uuid = Uuid::from_u128(3);
+
+    fn player(id: Uuid, name: &str, role: Role, position: (u16, u16), symbol: AvatarSymbol) -> Player {
+        Player {
+            id,
+            name: name.into(),
+            role,
+            position,
+            color: AvatarColor::Green,
+            symbol,
+            confirmed: false,
+            presence: Presence::Active,
+        }
+    }
+
+    // Alice runs the session, Bob votes, Carol watches.
+    fn room(phase: Phase) -> GameState {
+        let map = MapLayout::default();
+        let zone = |card| map.zone_for_card(card).unwrap().center();
+        let players = [
+            player(ALICE, "Alice", Role::ScrumMaster, map.spawn_point(0), AvatarSymbol::Cat),
+            player(BOB, "Bob", Role::Participant, zone(5), AvatarSymbol::Robot),
+            player(CAROL, "Carol", Role::Observer, map.spawn_point(2), AvatarSymbol::Star),
+        ];
+        GameState {
+            players: players.into_iter().map(|p| (p.id, p)).collect(),
+            phase,
+            current_ticket: None,
+            votes: HashMap::new(),
+            final_estimate: None,
+            backlog: Vec::new(),
+            config: VotingConfig::default(),
+            map,
+        }
+    }
+
+    fn app_as(self_id: Uuid, state: GameState) -> App {
+        let mut app = App::new();
+        app.current_screen = CurrentScreen::Main;
+        app.self_id = Some(self_id);
+        app.game_state = Some(state);
+        // Fixed lines; `App::log` would stamp them with the current time
+        app.logs = vec!["Connected to server v0.1.0".into()];
+        app
+    }
+
+    fn voting() -> Phase {
+        Phase::Voting { start_time_unix: 0, duration_secs: None }
+    }
+
+    fn render(app: &App, width: u16, height: u16) -> String {
+        let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+        terminal.draw(|f| ui(f, app)).unwrap();
+        terminal.backend().to_string()
+    }
+
+    // Regenerate with `UPDATE_SNAPSHOTS=1 cargo test -p client`.
+    fn assert_snapshot(name: &str, rendered: &str) {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/snapshots").join(format!("{}.txt", name));
+        if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(&path, rendered).unwrap();
+            return;
+        }
+        let on_disk = std::fs::read_to_string(&path).unwrap_or_default();
+        assert!(
+            on_disk == rendered,
+            "{} is out of date; run `UPDATE_SNAPSHOTS=1 cargo test -p client`\n{}",
+            path.display(),
+            rendered
+        );
+    }
+
+    #[test]
+    fn login_screen() {
+        let mut app = App::new();
+        app.color_input = AvatarColor::Blue;
+        app.symbol_input = AvatarSymbol::Dog;
+        app.name_input = "Alice".into();
+        app.connection_error = Some("Connection Failed: connection refused".into());
+        assert_snapshot("login", &render(&app, 80, 30));
+    }
+
+    #[test]
+    fn idle_room_for_the_scrum_master() {
+        let app = app_as(ALICE, room(Phase::Idle));
+        assert_snapshot("idle_scrum_master", &render(&app, 90, 37));
+    }
+
+    // Other avatars are hidden while voting; the ticket panel fits beside the whole map at this width.
+    #[test]
+    fn voting_as_a_participant() {
+        let mut state = room(voting());
+        state.current_ticket = Some(Ticket {
+            key: Some("acme/app#12".into()),
+            description: Some("SSO users see a blank page after logging in.".into()),
+            labels: vec!["bug".into()],
+            ..Ticket::new("Fix login")
+        });
+        state.votes.insert(BOB, Some(5));
+        state.votes.insert(ALICE, Some(8));
+        let mut app = app_as(BOB, state);
+        app.typed_digits = "1".into();
+        assert_snapshot("voting_participant", &render(&app, 122, 37));
+    }
+
+    #[test]
+    fn voting_as_an_observer() {
+        let mut state = room(voting());
+        state.votes.insert(BOB, Some(5));
+        let app = app_as(CAROL, state);
+        assert_snapshot("voting_observer", &render(&app, 90, 37));
+    }
+
+    #[test]
+    fn revealed_with_stats() {
+        let mut state = room(Phase::Revealed);
+        for (id, card) in [(ALICE, 8), (BOB, 5)] {
+            state.votes.insert(id, Some(card));
+            state.players.get_mut(&id).unwrap().confirmed = true;
+        }
+        state.final_estimate = Some(5);
+        let app = app_as(ALICE, state);
+        assert_snapshot("revealed", &render(&app, 90, 37));
+    }
+
+    #[test]
+    fn card_picker_over_the_room() {
+        let mut app = app_as(BOB, room(voting()));
+        app.modal = Some(Modal::CardPicker { selected: 4 });
+        assert_snapshot("card_picker", &render(&app, 90, 37));
+    }
+
+    // The server parks other avatars at 0, 0 while voting; that isn't shown as a place.
+    #[test]
+    fn player_details_while_voting() {
+        let mut state = room(voting());
+        state.players.get_mut(&BOB).unwrap().position = (0, 0);
+        let mut app = app_as(CAROL, state);
+        app.modal = Some(Modal::PlayerDetails(BOB));
+        let rendered = render(&app, 90, 37);
+        assert!(rendered.contains("Position: hidden until the reveal"), "{}", rendered);
+        assert_snapshot("player_details_voting", &rendered);
+    }
+
+    // The whole map always fits, so the smallest usable room is set by its size,
+    // and the ticket panel waits for room beside it rather than squeezing it.
+    #[test]
+    fn the_map_sets_the_minimum_size_and_is_never_squeezed() {
+        let mut state = room(voting());
+        state.current_ticket = Some(Ticket::new("Fix login"));
+        let (map_width, map_height) = (state.map.width, state.map.height);
+        let app = app_as(BOB, state);
+
+        let (width, height) = min_terminal_size(&app);
+        assert_eq!((width, height), (map_width * 2 + 2, map_height + 2 + STATUS_HEIGHT + BOTTOM_HEIGHT));
+        assert!(!too_small(Rect::new(0, 0, width, height), &app));
+        assert!(too_small(Rect::new(0, 0, width - 1, height), &app));
+        assert!(too_small(Rect::new(0, 0, width, height - 1), &app));
+
+        for terminal_width in [width, width + TICKET_PANEL_WIDTH - 1, width + TICKET_PANEL_WIDTH, 200] {
+            let layout = main_layout(Rect::new(0, 0, terminal_width, height), &app);
+            assert!(layout.map_inner.width >= map_width * 2 && layout.map_inner.height >= map_height);
+            assert_eq!(layout.ticket.is_some(), terminal_width >= width + TICKET_PANEL_WIDTH);
+        }
+    }
+
+    #[test]
+    fn terminal_too_small() {
+        let app = app_as(BOB, room(Phase::Idle));
+        assert_snapshot("too_small", &render(&app, 60, 20));
     }
 }
@@ -1,17 +1,18 @@
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::time::{SystemTime, UNIX_EPOCH};
 use uuid::Uuid;
 use rand::Rng;
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
 pub enum Role {
     ScrumMaster,
     Participant,
     Observer,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
 pub enum AvatarColor {
     Red,
     Green,
@@ -44,7 +45,7 @@ impl AvatarColor {
             Self::Bronze, Self::Lime, Self::Teal, Self::Indigo, Self::Violet, Self::Coral,
             Self::Crimson, Self::White
         ];
-        variants[rng.gen_range(0..variants.len())].clone()
+        variants[rng.random_range(0..variants.len())].clone()
     }
     
     pub fn next(&self) -> Self {
@@ -59,7 +60,7 @@ impl AvatarColor {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
 pub enum AvatarSymbol {
     Human,
     Alien,
@@ -92,7 +93,7 @@ impl AvatarSymbol {
             Self::Sun, Self::Heart, Self::Skull, Self::Smile, Self::Zap, Self::Anchor,
             Self::Music, Self::Globe
         ];
-        variants[rng.gen_range(0..variants.len())].clone()
+        variants[rng.random_range(0..variants.len())].clone()
     }
      pub fn next(&self) -> Self {
          let variants = [
@@ -106,7 +107,7 @@ impl AvatarSymbol {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Player {
     pub id: Uuid,
     pub name: String,
@@ -116,14 +117,63 @@ pub struct Player {
     pub symbol: AvatarSymbol,
     #[serde(default)] // For backward compatibility if needed, though we don't have persistence
     pub confirmed: bool,
+    #[serde(default)]
+    pub presence: Presence,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default, JsonSchema)]
+pub enum Presence {
+    #[default]
+    Active,
+    // Connected, but hasn't moved or voted for a while.
+    Idle,
+    // Connection lost; the player is dropped once the grace period runs out.
+    Disconnected,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
 pub struct Ticket {
     pub title: String,
+    // Issue reference in its tracker, e.g. "owner/repo#12" or "PROJ-7"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+    // Plain text; clients wrap it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    // Link to the issue in its tracker
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub labels: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<TrackerKind>,
+}
+
+impl Ticket {
+    // A ticket typed in by hand, not linked to any tracker.
+    pub fn new(title: impl Into<String>) -> Self {
+        Self { title: title.into(), key: None, description: None, url: None, labels: Vec::new(), source: None }
+    }
+
+    // "PROJ-7 Login fails" when there is a key, otherwise just the title.
+    pub fn display_name(&self) -> String {
+        match &self.key {
+            Some(key) if *key != self.title => format!("{} {}", key, self.title),
+            _ => self.title.clone(),
+        }
+    }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum TrackerKind {
+    Github,
+    Gitlab,
+    // Sent by the browser extension; there is no Jira write-back
+    Jira,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
 pub enum Phase {
     Idle,
     Voting {
@@ -133,7 +183,7 @@ pub enum Phase {
     Revealed,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct GameState {
     pub players: HashMap<Uuid, Player>,
     pub phase: Phase,
@@ -141,10 +191,84 @@ pub struct GameState {
     // PlayerId -> Vote value. 
     // On the wire, hidden votes should be masked unless Phase is Revealed.
     pub votes: HashMap<Uuid, Option<u32>>,
+    // What the team settled on for the current ticket, set after the reveal.
+    #[serde(default)]
+    pub final_estimate: Option<u32>,
+    // Tickets queued up for estimation, e.g. imported from a tracker.
+    #[serde(default)]
+    pub backlog: Vec<Ticket>,
     pub config: VotingConfig,
+    // Laid out by the server from the deck; clients draw it as given.
+    #[serde(default)]
+    pub map: MapLayout,
+}
+
+impl GameState {
+    /// Applies a single delta to this state. Returns `false` when the event
+    /// refers to a player we don't know about, which means our copy has
+    /// drifted and a fresh snapshot should be requested.
+    pub fn apply(&mut self, event: &StateEvent) -> bool {
+        match event {
+            StateEvent::PlayerJoined(player) => {
+                self.players.insert(player.id, player.clone());
+            }
+            StateEvent::PlayerLeft { player_id } => {
+                self.votes.remove(player_id);
+                return self.players.remove(player_id).is_some();
+            }
+            StateEvent::PlayerMoved { player_id, position } => {
+                match self.players.get_mut(player_id) {
+                    Some(p) => p.position = *position,
+                    None => return false,
+                }
+            }
+            StateEvent::PlayerConfirmed { player_id, confirmed } => {
+                match self.players.get_mut(player_id) {
+                    Some(p) => p.confirmed = *confirmed,
+                    None => return false,
+                }
+            }
+            StateEvent::PresenceChanged { player_id, presence } => {
+                match self.players.get_mut(player_id) {
+                    Some(p) => p.presence = *presence,
+                    None => return false,
+                }
+            }
+            StateEvent::VoteCast { player_id, value } => {
+                if !self.players.contains_key(player_id) {
+                    return false;
+                }
+                self.votes.insert(*player_id, *value);
+            }
+            StateEvent::PhaseChanged { phase, ticket } => {
+                self.phase = phase.clone();
+                self.current_ticket = ticket.clone();
+            }
+            StateEvent::VotesCleared => {
+                self.votes.clear();
+                self.final_estimate = None;
+                for p in self.players.values_mut() {
+                    p.confirmed = false;
+                }
+            }
+            StateEvent::EstimateSet { value } => {
+                self.final_estimate = *value;
+            }
+            StateEvent::BacklogChanged(tickets) => {
+                self.backlog = tickets.clone();
+            }
+            StateEvent::ConfigChanged(config) => {
+                self.config = config.clone();
+            }
+            StateEvent::MapChanged(map) => {
+                self.map = map.clone();
+            }
+        }
+        true
+    }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
 pub struct VotingConfig {
     pub cards: Vec<u32>,
     pub default_timeout: Option<u64>,
@@ -159,16 +283,168 @@ impl Default for VotingConfig {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+// A card's area on the map, in grid cells. Standing in it during a round is a vote for the card.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+pub struct Zone {
+    pub value: u32,
+    pub x: u16,
+    pub y: u16,
+    pub width: u16,
+    pub height: u16,
+}
+
+impl Zone {
+    pub fn contains(&self, x: u16, y: u16) -> bool {
+        x >= self.x && x < self.x + self.width && y >= self.y && y < self.y + self.height
+    }
+
+    // Where an avatar lands when voting by key instead of walking.
+    pub fn center(&self) -> (u16, u16) {
+        (self.x + self.width / 2, self.y + self.height / 2)
+    }
+}
+
+// The room's floor plan, in grid cells. Moves outside it are rejected.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+pub struct MapLayout {
+    pub width: u16,
+    pub height: u16,
+    pub zones: Vec<Zone>,
+    // Where players appear when they join or the round is reset
+    pub spawn_points: Vec<(u16, u16)>,
+}
+
+impl MapLayout {
+    // Room size the server lays decks out in; about 80x20 characters on screen
+    pub const WIDTH: u16 = 40;
+    pub const HEIGHT: u16 = 20;
+    const MARGIN: u16 = 2;
+    // Zones start below the spawn row, leaving room for players at the top
+    const ZONES_TOP: u16 = 4;
+    const ZONE_SIZE: u16 = 4;
+    // Smallest zone whose border still has room for a two-digit card
+    const MIN_ZONE_SIZE: u16 = 3;
+    const ZONE_GAP: u16 = 1;
+
+    pub fn for_cards(cards: &[u32]) -> Self {
+        Self::fit(cards, Self::WIDTH, Self::HEIGHT)
+    }
+
+    // Rows of zones in deck order, wrapped to fit `width`. Zones shrink when the
+    // rows would run past `height`; if even the smallest don't fit, the map grows
+    // taller rather than dropping cards. Rows are balanced so the last one isn't
+    // left with a single card.
+    pub fn fit(cards: &[u32], width: u16, height: u16) -> Self {
+        let width = width.max(2 * Self::MARGIN + Self::MIN_ZONE_SIZE);
+        let n = cards.len().max(1) as u16;
+        let rows_for = |size: u16| {
+            let per_row = ((width - 2 * Self::MARGIN + Self::ZONE_GAP) / (size + Self::ZONE_GAP)).max(1);
+            n.div_ceil(per_row)
+        };
+        let needed = |size: u16| Self::ZONES_TOP + rows_for(size) * (size + Self::ZONE_GAP) - Self::ZONE_GAP + Self::MARGIN;
+        let size = (Self::MIN_ZONE_SIZE..=Self::ZONE_SIZE)
+            .rev()
+            .find(|&size| needed(size) <= height)
+            .unwrap_or(Self::MIN_ZONE_SIZE);
+        let rows = rows_for(size);
+        let per_row = n.div_ceil(rows);
+        let step = size + Self::ZONE_GAP;
+
+        let zones: Vec<Zone> = cards
+            .iter()
+            .enumerate()
+            .map(|(i, &value)| {
+                let (row, col) = (i as u16 / per_row, i as u16 % per_row);
+                Zone {
+                    value,
+                    x: Self::MARGIN + col * step,
+                    y: Self::ZONES_TOP + row * step,
+                    width: size,
+                    height: size,
+                }
+            })
+            .collect();
+        // A spawn point above each column of zones
+        let mut spawn_points: Vec<(u16, u16)> = zones.iter().take(per_row as usize).map(|z| (z.center().0, 1)).collect();
+        if spawn_points.is_empty() {
+            spawn_points.push((Self::MARGIN, 1));
+        }
+        Self {
+            width,
+            height: height.max(needed(size)),
+            zones,
+            spawn_points,
+        }
+    }
+
+    pub fn contains(&self, x: u16, y: u16) -> bool {
+        x < self.width && y < self.height
+    }
+
+    pub fn zone_at(&self, x: u16, y: u16) -> Option<&Zone> {
+        self.zones.iter().find(|z| z.contains(x, y))
+    }
+
+    pub fn zone_for_card(&self, card: u32) -> Option<&Zone> {
+        self.zones.iter().find(|z| z.value == card)
+    }
+
+    // Spreads players over the spawn points, cycling when there are more players than points.
+    pub fn spawn_point(&self, n: usize) -> (u16, u16) {
+        self.spawn_points.get(n % self.spawn_points.len().max(1)).copied().unwrap_or((0, 0))
+    }
+}
+
+impl Default for MapLayout {
+    fn default() -> Self {
+        Self::for_cards(&VotingConfig::default().cards)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub enum ClientPayload {
-    Login { name: String, role: Role, color: AvatarColor, symbol: AvatarSymbol },
+    Login {
+        name: String,
+        role: Role,
+        color: AvatarColor,
+        symbol: AvatarSymbol,
+        // Token from a previous `Welcome`; reclaims that player if it's still in the room.
+        #[serde(default)]
+        resume_token: Option<Uuid>,
+    },
+    // During a round the server votes for whichever zone the new position is in.
     Move { x: u16, y: u16 },
+    // Votes without moving; kept for scripted clients, the TUI votes by moving.
     Vote { value: Option<u32> },
     VoteConfirm { confirmed: bool },
     Admin(AdminCommand),
+    // Heartbeat; the server answers with `Pong` carrying the same nonce.
+    Ping { nonce: u64 },
+    // Ask the server for a full `StateUpdate`, e.g. after deltas stopped lining up.
+    RequestSnapshot,
+}
+
+impl ClientPayload {
+    // Every value `kind()` can return.
+    pub const KINDS: [&'static str; 7] = [
+        "Login", "Move", "Vote", "VoteConfirm", "Admin", "Ping", "RequestSnapshot",
+    ];
+
+    // Variant name, for logs and metrics.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Login { .. } => "Login",
+            Self::Move { .. } => "Move",
+            Self::Vote { .. } => "Vote",
+            Self::VoteConfirm { .. } => "VoteConfirm",
+            Self::Admin(_) => "Admin",
+            Self::Ping { .. } => "Ping",
+            Self::RequestSnapshot => "RequestSnapshot",
+        }
+    }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub enum AdminCommand {
     StartVote {
         ticket: Option<Ticket>,
@@ -178,18 +454,141 @@ pub enum AdminCommand {
     Reset,
     Kick { player_id: Uuid },
     UpdateConfig(VotingConfig),
+    // Restart the countdown of the running round with a new duration.
+    SetTimer { timeout: Option<u64> },
+    // Change the ticket without touching the phase or votes.
+    SetTicket { ticket: Option<Ticket> },
+    // Start a fresh round on the current ticket.
+    Revote,
+    // Record the agreed estimate once votes are revealed; `None` clears it.
+    SetEstimate { value: Option<u32> },
+    // Replace the backlog.
+    SetBacklog { tickets: Vec<Ticket> },
+}
+
+impl AdminCommand {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::StartVote { .. } => "StartVote",
+            Self::Reveal => "Reveal",
+            Self::Reset => "Reset",
+            Self::Kick { .. } => "Kick",
+            Self::UpdateConfig(_) => "UpdateConfig",
+            Self::SetTimer { .. } => "SetTimer",
+            Self::SetTicket { .. } => "SetTicket",
+            Self::Revote => "Revote",
+            Self::SetEstimate { .. } => "SetEstimate",
+            Self::SetBacklog { .. } => "SetBacklog",
+        }
+    }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub enum ServerPayload {
-    Welcome { self_id: Uuid, state: GameState },
+    Welcome { self_id: Uuid, state: GameState, session_token: Uuid, server: ServerInfo },
+    // Full snapshot. Sent on request; everything else goes out as `Events`.
     StateUpdate(GameState),
-    Error(String),
+    Events(Vec<StateEvent>),
+    Pong { nonce: u64 },
+    // The server is going away; state is saved and can be resumed after a restart.
+    Shutdown { reason: String },
+    Error { code: ErrorCode, message: String },
+}
+
+// Why the server rejected a message. `message` in `ServerPayload::Error`
+// is for humans; clients should branch on the code.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+pub enum ErrorCode {
+    MalformedMessage,
+    FrameTooLarge,
+    RateLimited,
+    NotLoggedIn,
+    AlreadyLoggedIn,
+    InvalidName,
+    NameTaken,
+    InvalidCard,
+    NotVoting,
+    NotAuthorized,
+    UnknownPlayer,
+    InvalidConfig,
+    InvalidTicket,
+    NotRevealed,
+    Unavailable,
+    OutOfBounds,
+    // Sent to a player the Scrum Master removed, just before the server hangs up
+    Kicked,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+pub struct ServerInfo {
+    pub version: String,
+    pub protocol_version: u32,
 }
 
+// Incremental state changes, applied in order on top of the last snapshot.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub enum StateEvent {
+    PlayerJoined(Player),
+    PlayerLeft { player_id: Uuid },
+    PlayerMoved { player_id: Uuid, position: (u16, u16) },
+    PlayerConfirmed { player_id: Uuid, confirmed: bool },
+    PresenceChanged { player_id: Uuid, presence: Presence },
+    VoteCast { player_id: Uuid, value: Option<u32> },
+    PhaseChanged { phase: Phase, ticket: Option<Ticket> },
+    // Drops every vote and the final estimate, and un-confirms every player.
+    VotesCleared,
+    EstimateSet { value: Option<u32> },
+    BacklogChanged(Vec<Ticket>),
+    ConfigChanged(VotingConfig),
+    MapChanged(MapLayout),
+}
+
+// Bumped whenever `ClientPayload`/`ServerPayload` change incompatibly.
+pub const PROTOCOL_VERSION: u32 = 2;
+
+pub const MAX_NAME_LEN: usize = 24;
+pub const MAX_CARDS: usize = 20;
+pub const MAX_BACKLOG: usize = 100;
+// Ticket limits, in characters; trackers' own title limits are around 255
+pub const MAX_TICKET_TITLE_LEN: usize = 256;
+pub const MAX_TICKET_DESCRIPTION_LEN: usize = 4000;
+pub const MAX_TICKET_LABELS: usize = 20;
+pub const MAX_TICKET_LABEL_LEN: usize = 64;
+pub const MAX_TICKET_KEY_LEN: usize = 256;
+pub const MAX_TICKET_URL_LEN: usize = 2048;
+
+// Most bytes one valid ticket can take as JSON. Any character may come out
+// as a six-byte `\uXXXX` escape; the rest covers field names and punctuation.
+pub const MAX_TICKET_JSON_BYTES: usize = 6
+    * (MAX_TICKET_TITLE_LEN
+        + MAX_TICKET_DESCRIPTION_LEN
+        + MAX_TICKET_KEY_LEN
+        + MAX_TICKET_URL_LEN
+        + MAX_TICKET_LABELS * MAX_TICKET_LABEL_LEN)
+    + 512;
+// Longest line (one JSON message) the server will read: enough for a
+// `SetBacklog` with a full backlog of the largest tickets.
+pub const MAX_FRAME_BYTES: usize = MAX_BACKLOG * MAX_TICKET_JSON_BYTES + 1024;
+// Longest line clients should accept from the server. Snapshots carry the
+// whole backlog along with the current ticket, players and map.
+pub const MAX_SERVER_FRAME_BYTES: usize = MAX_FRAME_BYTES + 1024 * 1024;
+
+// How often clients ping the server. Servers drop connections that stay
+// silent for a few intervals, and clients give up on a server the same way.
+pub const HEARTBEAT_INTERVAL_SECS: u64 = 5;
+
 pub fn current_time_unix() -> u64 {
     SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .unwrap_or_default()
         .as_secs()
 }
+
+// JSON Schemas for the TCP protocol, one per line direction. The checked-in
+// copies under `common/schemas/` are compared against these by the tests.
+pub fn protocol_schemas() -> Vec<(&'static str, schemars::Schema)> {
+    vec![
+        ("client_payload", schemars::schema_for!(ClientPayload)),
+        ("server_payload", schemars::schema_for!(ServerPayload)),
+    ]
+}
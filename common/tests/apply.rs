@@ -0,0 +1,115 @@
+// Clients keep their copy of the room up to date by replaying deltas with
+// `GameState::apply`; a `false` from it is their cue to ask for a snapshot.
+
+use std::collections::HashMap;
+
+use common::{
+    AvatarColor, AvatarSymbol, GameState, MapLayout, Phase, Player, Presence, Role, StateEvent, Ticket, VotingConfig,
+};
+use uuid::Uuid;
+
+const ALICE: Uuid = Uuid::from_u128(1);
+const BOB: Uuid = Uuid::from_u128(2);
+const STRANGER: Uuid = Uuid::from_u128(99);
+
+fn player(id: Uuid, name: &str) -> Player {
+    Player {
+        id,
+        name: name.into(),
+        role: Role::Participant,
+        position: (1, 1),
+        color: AvatarColor::Red,
+        symbol: AvatarSymbol::Cat,
+        confirmed: false,
+        presence: Presence::Active,
+    }
+}
+
+// Alice and Bob in a room that is voting.
+fn room() -> GameState {
+    let mut state = GameState {
+        players: HashMap::new(),
+        phase: Phase::Voting { start_time_unix: 0, duration_secs: None },
+        current_ticket: None,
+        votes: HashMap::new(),
+        final_estimate: None,
+        backlog: Vec::new(),
+        config: VotingConfig::default(),
+        map: MapLayout::default(),
+    };
+    assert!(state.apply(&StateEvent::PlayerJoined(player(ALICE, "Alice"))));
+    assert!(state.apply(&StateEvent::PlayerJoined(player(BOB, "Bob"))));
+    state
+}
+
+#[test]
+fn player_events_update_that_player() {
+    let mut state = room();
+    assert!(state.apply(&StateEvent::PlayerMoved { player_id: ALICE, position: (7, 3) }));
+    assert!(state.apply(&StateEvent::PlayerConfirmed { player_id: ALICE, confirmed: true }));
+    assert!(state.apply(&StateEvent::PresenceChanged { player_id: ALICE, presence: Presence::Idle }));
+    assert!(state.apply(&StateEvent::VoteCast { player_id: ALICE, value: Some(5) }));
+
+    let alice = &state.players[&ALICE];
+    assert_eq!((alice.position, alice.confirmed, alice.presence), ((7, 3), true, Presence::Idle));
+    assert_eq!(state.votes.get(&ALICE), Some(&Some(5)));
+    assert_eq!(state.players[&BOB].position, (1, 1));
+
+    // Leaving takes the vote along
+    assert!(state.apply(&StateEvent::PlayerLeft { player_id: ALICE }));
+    assert!(!state.players.contains_key(&ALICE));
+    assert!(!state.votes.contains_key(&ALICE));
+}
+
+#[test]
+fn round_events_update_the_room() {
+    let mut state = room();
+    let ticket = Ticket::new("Export CSV");
+    assert!(state.apply(&StateEvent::PhaseChanged { phase: Phase::Revealed, ticket: Some(ticket.clone()) }));
+    assert_eq!(state.phase, Phase::Revealed);
+    assert_eq!(state.current_ticket, Some(ticket));
+
+    state.apply(&StateEvent::VoteCast { player_id: BOB, value: Some(3) });
+    state.apply(&StateEvent::PlayerConfirmed { player_id: BOB, confirmed: true });
+    assert!(state.apply(&StateEvent::EstimateSet { value: Some(3) }));
+    assert_eq!(state.final_estimate, Some(3));
+
+    assert!(state.apply(&StateEvent::VotesCleared));
+    assert!(state.votes.is_empty());
+    assert_eq!(state.final_estimate, None);
+    assert!(state.players.values().all(|p| !p.confirmed));
+}
+
+#[test]
+fn room_wide_settings_are_replaced() {
+    let mut state = room();
+    let backlog = vec![Ticket::new("One"), Ticket::new("Two")];
+    assert!(state.apply(&StateEvent::BacklogChanged(backlog.clone())));
+    assert_eq!(state.backlog, backlog);
+
+    let config = VotingConfig { cards: vec![1, 2, 4], default_timeout: None };
+    assert!(state.apply(&StateEvent::ConfigChanged(config.clone())));
+    assert_eq!(state.config, config);
+
+    let map = MapLayout::for_cards(&config.cards);
+    assert!(state.apply(&StateEvent::MapChanged(map.clone())));
+    assert_eq!(state.map, map);
+}
+
+#[test]
+fn events_about_unknown_players_report_drift() {
+    let mut state = room();
+    let drifted = [
+        StateEvent::PlayerLeft { player_id: STRANGER },
+        StateEvent::PlayerMoved { player_id: STRANGER, position: (2, 2) },
+        StateEvent::PlayerConfirmed { player_id: STRANGER, confirmed: true },
+        StateEvent::PresenceChanged { player_id: STRANGER, presence: Presence::Idle },
+        StateEvent::VoteCast { player_id: STRANGER, value: Some(1) },
+    ];
+    for event in &drifted {
+        assert!(!state.apply(event), "{:?} should report drift", event);
+    }
+    // Nothing about the stranger was made up along the way
+    assert!(!state.players.contains_key(&STRANGER));
+    assert!(!state.votes.contains_key(&STRANGER));
+}
@@ -0,0 +1,61 @@
+use common::{MapLayout, Zone, MAX_CARDS};
+use proptest::prelude::*;
+
+fn overlaps(a: &Zone, b: &Zone) -> bool {
+    a.x < b.x + b.width && b.x < a.x + a.width && a.y < b.y + b.height && b.y < a.y + a.height
+}
+
+fn deck(max: usize) -> impl Strategy<Value = Vec<u32>> {
+    prop::collection::btree_set(0u32..1000, 1..=max).prop_map(|cards| cards.into_iter().collect())
+}
+
+proptest! {
+    #[test]
+    fn zones_never_overlap_and_stay_on_the_map(cards in deck(64), width in 0u16..200, height in 0u16..60) {
+        let map = MapLayout::fit(&cards, width, height);
+        let values: Vec<u32> = map.zones.iter().map(|z| z.value).collect();
+        prop_assert_eq!(values, cards);
+        for (i, a) in map.zones.iter().enumerate() {
+            prop_assert!(a.x + a.width <= map.width && a.y + a.height <= map.height, "{:?} off a {}x{} map", a, map.width, map.height);
+            for b in &map.zones[i + 1..] {
+                prop_assert!(!overlaps(a, b), "{:?} overlaps {:?}", a, b);
+            }
+        }
+        for &(x, y) in &map.spawn_points {
+            prop_assert!(map.contains(x, y));
+            prop_assert!(map.zone_at(x, y).is_none());
+        }
+    }
+
+    #[test]
+    fn any_valid_deck_fits_the_room(cards in deck(MAX_CARDS)) {
+        let map = MapLayout::for_cards(&cards);
+        prop_assert_eq!((map.width, map.height), (MapLayout::WIDTH, MapLayout::HEIGHT));
+    }
+
+    #[test]
+    fn layout_only_grows_past_the_requested_size_when_it_must(cards in deck(64), width in 10u16..200, height in 10u16..60) {
+        let map = MapLayout::fit(&cards, width, height);
+        prop_assert_eq!(map.width, width);
+        if map.height > height {
+            // Already at the smallest zones
+            prop_assert!(map.zones.iter().all(|z| z.width == map.zones[0].width));
+            prop_assert_eq!(map.zones[0].width, 3);
+        }
+    }
+}
+
+#[test]
+fn small_decks_keep_one_row_of_full_size_zones() {
+    let map = MapLayout::for_cards(&[0, 1, 2, 3, 5, 8, 13]);
+    assert!(map.zones.iter().all(|z| z.y == map.zones[0].y && z.width == 4));
+}
+
+#[test]
+fn large_decks_wrap_into_balanced_rows() {
+    let cards: Vec<u32> = (1..=10).collect();
+    let map = MapLayout::for_cards(&cards);
+    let first_row = map.zones.iter().filter(|z| z.y == map.zones[0].y).count();
+    assert_eq!(first_row, 5);
+    assert_eq!(map.zones[5].x, map.zones[0].x);
+}
@@ -0,0 +1,129 @@
+use common::*;
+use std::path::PathBuf;
+use uuid::Uuid;
+
+fn schema_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("schemas").join(format!("{}.schema.json", name))
+}
+
+// Regenerate with `UPDATE_SCHEMAS=1 cargo test -p common --test schemas`.
+#[test]
+fn checked_in_schemas_match_types() {
+    let update = std::env::var_os("UPDATE_SCHEMAS").is_some();
+    for (name, schema) in protocol_schemas() {
+        let generated = serde_json::to_string_pretty(&schema).unwrap() + "\n";
+        let path = schema_path(name);
+        if update {
+            std::fs::write(&path, &generated).unwrap();
+            continue;
+        }
+        let on_disk = std::fs::read_to_string(&path).unwrap_or_default();
+        assert!(
+            on_disk == generated,
+            "{} is out of date; run `UPDATE_SCHEMAS=1 cargo test -p common --test schemas`",
+            path.display()
+        );
+    }
+}
+
+fn validator(name: &str) -> jsonschema::Validator {
+    let schema = protocol_schemas().into_iter().find(|(n, _)| *n == name).unwrap().1;
+    jsonschema::validator_for(&schema.to_value()).unwrap()
+}
+
+fn assert_valid(validator: &jsonschema::Validator, value: &impl serde::Serialize) {
+    let json = serde_json::to_value(value).unwrap();
+    let errors: Vec<String> = validator.iter_errors(&json).map(|e| e.to_string()).collect();
+    assert!(errors.is_empty(), "{} does not match schema: {:?}", json, errors);
+}
+
+#[test]
+fn client_payloads_match_schema() {
+    let validator = validator("client_payload");
+    let payloads = vec![
+        ClientPayload::Login {
+            name: "Alice".into(),
+            role: Role::ScrumMaster,
+            color: AvatarColor::Blue,
+            symbol: AvatarSymbol::Robot,
+            resume_token: Some(Uuid::new_v4()),
+        },
+        ClientPayload::Move { x: 3, y: 4 },
+        ClientPayload::Vote { value: Some(5) },
+        ClientPayload::Vote { value: None },
+        ClientPayload::VoteConfirm { confirmed: true },
+        ClientPayload::Ping { nonce: 7 },
+        ClientPayload::RequestSnapshot,
+        ClientPayload::Admin(AdminCommand::StartVote { ticket: Some(Ticket::new("PROJ-1")), timeout: Some(60) }),
+        ClientPayload::Admin(AdminCommand::Reveal),
+        ClientPayload::Admin(AdminCommand::Kick { player_id: Uuid::new_v4() }),
+        ClientPayload::Admin(AdminCommand::UpdateConfig(VotingConfig::default())),
+        ClientPayload::Admin(AdminCommand::Revote),
+        ClientPayload::Admin(AdminCommand::SetEstimate { value: Some(8) }),
+        ClientPayload::Admin(AdminCommand::SetBacklog { tickets: vec![Ticket::new("Spike")] }),
+    ];
+    for payload in &payloads {
+        assert_valid(&validator, payload);
+    }
+
+    // A message the server would fail to parse must fail validation too
+    let bogus = serde_json::json!({ "Move": { "x": "left" } });
+    assert!(!validator.is_valid(&bogus));
+}
+
+#[test]
+fn server_payloads_match_schema() {
+    let validator = validator("server_payload");
+    let mut state = GameState {
+        players: Default::default(),
+        phase: Phase::Idle,
+        current_ticket: None,
+        votes: Default::default(),
+        final_estimate: None,
+        backlog: vec![Ticket {
+            key: Some("acme/app#12".into()),
+            description: Some("SSO users see a blank page.".into()),
+            url: Some("https://github.com/acme/app/issues/12".into()),
+            labels: vec!["bug".into()],
+            source: Some(TrackerKind::Github),
+            ..Ticket::new("Fix login")
+        }],
+        config: VotingConfig::default(),
+        map: MapLayout::default(),
+    };
+    let player = Player {
+        id: Uuid::new_v4(),
+        name: "Bob".into(),
+        role: Role::Participant,
+        position: (1, 2),
+        color: AvatarColor::random(),
+        symbol: AvatarSymbol::random(),
+        confirmed: false,
+        presence: Presence::Idle,
+    };
+    state.apply(&StateEvent::PlayerJoined(player.clone()));
+    state.apply(&StateEvent::VoteCast { player_id: player.id, value: Some(8) });
+
+    let payloads = vec![
+        ServerPayload::Welcome {
+            self_id: player.id,
+            state: state.clone(),
+            session_token: Uuid::new_v4(),
+            server: ServerInfo { version: "0.1.0".into(), protocol_version: PROTOCOL_VERSION },
+        },
+        ServerPayload::StateUpdate(state),
+        ServerPayload::Events(vec![
+            StateEvent::PlayerMoved { player_id: player.id, position: (5, 5) },
+            StateEvent::PhaseChanged { phase: Phase::Voting { start_time_unix: 1, duration_secs: None }, ticket: None },
+            StateEvent::VotesCleared,
+            StateEvent::EstimateSet { value: Some(5) },
+            StateEvent::MapChanged(MapLayout::for_cards(&[1, 2, 3])),
+        ]),
+        ServerPayload::Pong { nonce: 1 },
+        ServerPayload::Shutdown { reason: "bye".into() },
+        ServerPayload::Error { code: ErrorCode::NotVoting, message: "No vote in progress".into() },
+    ];
+    for payload in &payloads {
+        assert_valid(&validator, payload);
+    }
+}
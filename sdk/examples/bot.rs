@@ -0,0 +1,43 @@
+// A participant that walks to a random card each round and confirms it.
+//
+//     cargo run -p sdk --example bot -- [addr] [name]
+
+use std::time::Duration;
+
+use rand::seq::IndexedRandom;
+use sdk::common::{Phase, Role};
+use sdk::{Login, Session};
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut args = std::env::args().skip(1);
+    let addr = args.next().unwrap_or_else(|| "127.0.0.1:8888".to_string());
+    let name = args.next().unwrap_or_else(|| "Bot".to_string());
+
+    let mut session = Session::login(addr.as_str(), Login::new(name, Role::Participant)).await?;
+    println!("joined as {}", session.me().map(|p| p.name.as_str()).unwrap_or("?"));
+
+    let mut heartbeat = tokio::time::interval(Duration::from_secs(1));
+    let mut voted = false;
+    loop {
+        tokio::select! {
+            payload = session.next() => {
+                payload?;
+            }
+            _ = heartbeat.tick() => {
+                if !session.connection().heartbeat() {
+                    return Err("server stopped responding".into());
+                }
+            }
+        }
+
+        let voting = matches!(session.state().phase, Phase::Voting { .. });
+        if voting && !voted {
+            let card = *session.state().config.cards.choose(&mut rand::rng()).expect("deck is never empty");
+            println!("voting {}", card);
+            session.walk_to_card(card)?;
+            session.confirm(true)?;
+        }
+        voted = voting;
+    }
+}
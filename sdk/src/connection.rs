@@ -0,0 +1,146 @@
+use std::time::{Duration, Instant};
+
+use common::{ClientPayload, ErrorCode, ServerPayload, HEARTBEAT_INTERVAL_SECS, MAX_SERVER_FRAME_BYTES};
+use futures::{SinkExt, StreamExt};
+use tokio::net::{TcpStream, ToSocketAddrs};
+use tokio::sync::mpsc;
+use tokio::sync::mpsc::error::TryRecvError;
+use tokio_util::codec::{Framed, LinesCodec};
+
+// Give up on a server that hasn't said anything (not even a pong) for this many heartbeats.
+const MISSED_HEARTBEATS: u32 = 3;
+
+#[derive(Debug)]
+pub enum Error {
+    // Could not reach the server
+    Connect(std::io::Error),
+    // The connection is gone
+    Closed,
+    // The server answered with `ServerPayload::Error`
+    Rejected { code: ErrorCode, message: String },
+    // The server is going away; the session can be resumed once it is back
+    Shutdown(String),
+    // Nothing arrived in time
+    Timeout,
+    // The server sent something unreadable, e.g. a line over `MAX_SERVER_FRAME_BYTES`;
+    // the connection is closed after this
+    Protocol(String),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Connect(e) => write!(f, "{}", e),
+            Self::Closed => write!(f, "connection closed"),
+            Self::Rejected { code, message } => write!(f, "rejected ({:?}): {}", code, message),
+            Self::Shutdown(reason) => write!(f, "server shutting down: {}", reason),
+            Self::Timeout => write!(f, "timed out waiting for the server"),
+            Self::Protocol(e) => write!(f, "protocol error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+// One TCP connection to the server, one JSON message per line. Reading and
+// writing happen on background tasks, so sending never blocks.
+pub struct Connection {
+    tx: mpsc::UnboundedSender<ClientPayload>,
+    rx: mpsc::UnboundedReceiver<Result<ServerPayload, Error>>,
+    last_received: Instant,
+    last_ping: Instant,
+    next_nonce: u64,
+}
+
+impl Connection {
+    pub async fn connect(addr: impl ToSocketAddrs) -> Result<Self, Error> {
+        let stream = TcpStream::connect(addr).await.map_err(Error::Connect)?;
+        let framed = Framed::new(stream, LinesCodec::new_with_max_length(MAX_SERVER_FRAME_BYTES));
+        let (mut sink, mut stream) = framed.split();
+
+        let (tx_out, mut rx_out) = mpsc::unbounded_channel::<ClientPayload>();
+        let (tx_in, rx_in) = mpsc::unbounded_channel::<Result<ServerPayload, Error>>();
+
+        tokio::spawn(async move {
+            while let Some(payload) = rx_out.recv().await {
+                let json = serde_json::to_string(&payload).expect("client payloads serialize");
+                if sink.send(json).await.is_err() {
+                    break;
+                }
+            }
+        });
+
+        // Also stops once the `Connection` is dropped, so the socket closes
+        // even if the server has nothing more to say
+        tokio::spawn(async move {
+            loop {
+                let line = tokio::select! {
+                    line = stream.next() => line,
+                    _ = tx_in.closed() => break,
+                };
+                let line = match line {
+                    Some(Ok(line)) => line,
+                    Some(Err(e)) => {
+                        let _ = tx_in.send(Err(Error::Protocol(e.to_string())));
+                        break;
+                    }
+                    None => break,
+                };
+                // Skip what we can't parse, e.g. a message added by a newer server
+                if let Ok(payload) = serde_json::from_str::<ServerPayload>(&line) {
+                    let _ = tx_in.send(Ok(payload));
+                }
+            }
+        });
+
+        Ok(Self {
+            tx: tx_out,
+            rx: rx_in,
+            last_received: Instant::now(),
+            last_ping: Instant::now(),
+            next_nonce: 0,
+        })
+    }
+
+    pub fn send(&self, payload: ClientPayload) -> Result<(), Error> {
+        self.tx.send(payload).map_err(|_| Error::Closed)
+    }
+
+    // The next message from the server.
+    pub async fn recv(&mut self) -> Result<ServerPayload, Error> {
+        let payload = self.rx.recv().await.ok_or(Error::Closed)??;
+        self.last_received = Instant::now();
+        Ok(payload)
+    }
+
+    // A message that has already arrived, if any.
+    pub fn try_recv(&mut self) -> Result<Option<ServerPayload>, Error> {
+        match self.rx.try_recv() {
+            Ok(payload) => {
+                self.last_received = Instant::now();
+                payload.map(Some)
+            }
+            Err(TryRecvError::Empty) => Ok(None),
+            Err(TryRecvError::Disconnected) => Err(Error::Closed),
+        }
+    }
+
+    // Send a ping and return its nonce; the server answers with a `Pong` carrying it.
+    pub fn ping(&mut self) -> Result<u64, Error> {
+        self.last_ping = Instant::now();
+        self.next_nonce += 1;
+        self.send(ClientPayload::Ping { nonce: self.next_nonce })?;
+        Ok(self.next_nonce)
+    }
+
+    // Send a ping if one is due; call it every second or so. Returns false once
+    // the server has gone quiet for too long, which usually means a dead or
+    // half-open connection.
+    pub fn heartbeat(&mut self) -> bool {
+        let interval = Duration::from_secs(HEARTBEAT_INTERVAL_SECS);
+        if self.last_ping.elapsed() >= interval {
+            let _ = self.ping();
+        }
+        self.last_received.elapsed() < interval * MISSED_HEARTBEATS
+    }
+}
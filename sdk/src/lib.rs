@@ -0,0 +1,10 @@
+//! Typed async client for the poker planning server, for bots, scripts and
+//! tests. `Connection` handles the framing and parsing; `Session` adds the
+//! login handshake and keeps a copy of the room up to date.
+
+mod connection;
+mod session;
+
+pub use common;
+pub use connection::{Connection, Error};
+pub use session::{Login, Session};
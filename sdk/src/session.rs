@@ -0,0 +1,181 @@
+use std::time::Duration;
+
+use common::{
+    AdminCommand, AvatarColor, AvatarSymbol, ClientPayload, GameState, Player, Role, ServerInfo, ServerPayload, Ticket,
+};
+use tokio::net::ToSocketAddrs;
+use uuid::Uuid;
+
+use crate::connection::{Connection, Error};
+
+// How long `Session::login` waits for the server's `Welcome`.
+const LOGIN_TIMEOUT: Duration = Duration::from_secs(10);
+
+// Who to log in as.
+#[derive(Debug, Clone)]
+pub struct Login {
+    pub name: String,
+    pub role: Role,
+    pub color: AvatarColor,
+    pub symbol: AvatarSymbol,
+    // `Session::session_token` from an earlier login; reclaims that player
+    pub resume_token: Option<Uuid>,
+}
+
+impl Login {
+    // A random avatar, and a fresh player.
+    pub fn new(name: impl Into<String>, role: Role) -> Self {
+        Self {
+            name: name.into(),
+            role,
+            color: AvatarColor::random(),
+            symbol: AvatarSymbol::random(),
+            resume_token: None,
+        }
+    }
+}
+
+impl From<Login> for ClientPayload {
+    fn from(login: Login) -> Self {
+        ClientPayload::Login {
+            name: login.name,
+            role: login.role,
+            color: login.color,
+            symbol: login.symbol,
+            resume_token: login.resume_token,
+        }
+    }
+}
+
+// A logged-in player, with the room as the server shows it to them. The
+// copy only moves forward when `next` (or something built on it) is awaited.
+pub struct Session {
+    conn: Connection,
+    pub self_id: Uuid,
+    pub session_token: Uuid,
+    pub server: ServerInfo,
+    state: GameState,
+}
+
+impl Session {
+    // Connect and log in. A refused login comes back as `Error::Rejected`.
+    pub async fn login(addr: impl ToSocketAddrs, login: Login) -> Result<Self, Error> {
+        let mut conn = Connection::connect(addr).await?;
+        conn.send(login.into())?;
+        tokio::time::timeout(LOGIN_TIMEOUT, async {
+            loop {
+                match conn.recv().await? {
+                    ServerPayload::Welcome { self_id, state, session_token, server } => {
+                        return Ok((self_id, state, session_token, server));
+                    }
+                    ServerPayload::Error { code, message } => return Err(Error::Rejected { code, message }),
+                    ServerPayload::Shutdown { reason } => return Err(Error::Shutdown(reason)),
+                    _ => {}
+                }
+            }
+        })
+        .await
+        .map_err(|_| Error::Timeout)?
+        .map(|(self_id, state, session_token, server)| Self { conn, self_id, session_token, server, state })
+    }
+
+    pub fn state(&self) -> &GameState {
+        &self.state
+    }
+
+    pub fn me(&self) -> Option<&Player> {
+        self.state.players.get(&self.self_id)
+    }
+
+    // For pings and anything the helpers below don't cover.
+    pub fn connection(&mut self) -> &mut Connection {
+        &mut self.conn
+    }
+
+    // The next message, already applied to `state()`. Deltas that don't line
+    // up with our copy trigger a resync, which arrives as a later `StateUpdate`.
+    pub async fn next(&mut self) -> Result<ServerPayload, Error> {
+        let payload = self.conn.recv().await?;
+        match &payload {
+            ServerPayload::StateUpdate(state) => self.state = state.clone(),
+            ServerPayload::Events(events) if !events.iter().all(|e| self.state.apply(e)) => {
+                self.conn.send(ClientPayload::RequestSnapshot)?;
+            }
+            _ => {}
+        }
+        Ok(payload)
+    }
+
+    // Follow the room until `done` holds. Errors and shutdown notices that
+    // arrive meanwhile end the wait.
+    pub async fn wait_for(&mut self, mut done: impl FnMut(&GameState) -> bool) -> Result<(), Error> {
+        while !done(&self.state) {
+            match self.next().await? {
+                ServerPayload::Error { code, message } => return Err(Error::Rejected { code, message }),
+                ServerPayload::Shutdown { reason } => return Err(Error::Shutdown(reason)),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    // Round-trip a ping. The server handles messages in order, so once this
+    // returns, everything our earlier messages caused has been applied.
+    pub async fn sync(&mut self) -> Result<(), Error> {
+        let nonce = self.conn.ping()?;
+        loop {
+            match self.next().await? {
+                ServerPayload::Pong { nonce: n } if n == nonce => return Ok(()),
+                ServerPayload::Error { code, message } => return Err(Error::Rejected { code, message }),
+                ServerPayload::Shutdown { reason } => return Err(Error::Shutdown(reason)),
+                _ => {}
+            }
+        }
+    }
+
+    pub fn move_to(&self, x: u16, y: u16) -> Result<(), Error> {
+        self.conn.send(ClientPayload::Move { x, y })
+    }
+
+    // Walk into `card`'s zone, the way the terminal client votes. Does
+    // nothing if the deck has no such card.
+    pub fn walk_to_card(&self, card: u32) -> Result<(), Error> {
+        match self.state.map.zone_for_card(card) {
+            Some(zone) => {
+                let (x, y) = zone.center();
+                self.move_to(x, y)
+            }
+            None => Ok(()),
+        }
+    }
+
+    // Vote without moving; `None` withdraws the vote.
+    pub fn vote(&self, value: Option<u32>) -> Result<(), Error> {
+        self.conn.send(ClientPayload::Vote { value })
+    }
+
+    pub fn confirm(&self, confirmed: bool) -> Result<(), Error> {
+        self.conn.send(ClientPayload::VoteConfirm { confirmed })
+    }
+
+    // Scrum Master only; anyone else gets `ErrorCode::NotAuthorized` back.
+    pub fn admin(&self, cmd: AdminCommand) -> Result<(), Error> {
+        self.conn.send(ClientPayload::Admin(cmd))
+    }
+
+    pub fn start_round(&self, ticket: Option<Ticket>, timeout: Option<u64>) -> Result<(), Error> {
+        self.admin(AdminCommand::StartVote { ticket, timeout })
+    }
+
+    pub fn reveal(&self) -> Result<(), Error> {
+        self.admin(AdminCommand::Reveal)
+    }
+
+    pub fn reset(&self) -> Result<(), Error> {
+        self.admin(AdminCommand::Reset)
+    }
+
+    pub fn kick(&self, player_id: Uuid) -> Result<(), Error> {
+        self.admin(AdminCommand::Kick { player_id })
+    }
+}
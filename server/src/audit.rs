@@ -0,0 +1,134 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+
+// Only the newest entries are kept in memory for queries; the file has everything.
+const MAX_IN_MEMORY: usize = 10_000;
+const DEFAULT_QUERY_LIMIT: usize = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum AuditSource {
+    Tcp,
+    Http,
+}
+
+// One admin action or HTTP API call: who, what, when, and how it went.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct AuditEntry {
+    pub timestamp_unix: u64,
+    pub actor: String,
+    pub source: AuditSource,
+    pub command: String,
+    pub details: serde_json::Value,
+    pub ok: bool,
+    pub error: Option<String>,
+}
+
+impl AuditEntry {
+    pub fn new(
+        actor: String,
+        source: AuditSource,
+        command: &str,
+        details: serde_json::Value,
+        result: Result<(), String>,
+    ) -> Self {
+        Self {
+            timestamp_unix: common::current_time_unix(),
+            actor,
+            source,
+            command: command.to_string(),
+            details,
+            ok: result.is_ok(),
+            error: result.err(),
+        }
+    }
+}
+
+// Filters for `GET /api/audit`. Matches are returned oldest first.
+#[derive(Debug, Default, Clone, Deserialize)]
+pub struct AuditQuery {
+    // Substring match, case-insensitive
+    pub actor: Option<String>,
+    pub command: Option<String>,
+    pub since: Option<u64>,
+    pub limit: Option<usize>,
+}
+
+// Append-only JSON-lines log. Owned by the room task, so there is one writer.
+pub struct AuditLog {
+    file: Option<File>,
+    entries: VecDeque<AuditEntry>,
+}
+
+impl AuditLog {
+    // Load previous entries and open the file for appending. If the file
+    // can't be opened the log still works, just without persistence.
+    pub fn open(path: &Path) -> Self {
+        let mut entries = VecDeque::new();
+        if let Ok(existing) = File::open(path) {
+            for line in BufReader::new(existing).lines().map_while(Result::ok) {
+                if let Ok(entry) = serde_json::from_str::<AuditEntry>(&line) {
+                    entries.push_back(entry);
+                }
+            }
+            let excess = entries.len().saturating_sub(MAX_IN_MEMORY);
+            entries.drain(..excess);
+        }
+
+        let file = match OpenOptions::new().create(true).append(true).open(path) {
+            Ok(file) => Some(file),
+            Err(e) => {
+                tracing::error!(path = %path.display(), error = %e, "cannot open audit log, entries will not be persisted");
+                None
+            }
+        };
+
+        Self { file, entries }
+    }
+
+    pub fn record(&mut self, entry: AuditEntry) {
+        tracing::info!(
+            target: "audit",
+            actor = %entry.actor,
+            source = ?entry.source,
+            command = %entry.command,
+            ok = entry.ok,
+            error = entry.error.as_deref().unwrap_or(""),
+            "audit entry"
+        );
+
+        if let Some(file) = &mut self.file {
+            let line = serde_json::to_string(&entry).unwrap();
+            if let Err(e) = writeln!(file, "{}", line) {
+                tracing::error!(error = %e, "failed to append to audit log");
+            }
+        }
+
+        self.entries.push_back(entry);
+        if self.entries.len() > MAX_IN_MEMORY {
+            self.entries.pop_front();
+        }
+    }
+
+    pub fn query(&self, query: &AuditQuery) -> Vec<AuditEntry> {
+        let actor = query.actor.as_ref().map(|a| a.to_lowercase());
+        let limit = query.limit.unwrap_or(DEFAULT_QUERY_LIMIT);
+
+        let mut matches: Vec<AuditEntry> = self
+            .entries
+            .iter()
+            .rev()
+            .filter(|e| actor.as_ref().is_none_or(|a| e.actor.to_lowercase().contains(a)))
+            .filter(|e| query.command.as_ref().is_none_or(|c| e.command.eq_ignore_ascii_case(c)))
+            .filter(|e| query.since.is_none_or(|since| e.timestamp_unix >= since))
+            .take(limit)
+            .cloned()
+            .collect();
+        matches.reverse();
+        matches
+    }
+}
@@ -0,0 +1,215 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use tokio::sync::broadcast;
+
+use crate::events::{RoundEvent, RoundEventKind};
+use crate::settings::Settings;
+use crate::webhooks::{forward_events, DeliveryLog, Poster};
+
+// Used when POKER_CHAT_TEMPLATE_PATH isn't set. Slack and Mattermost both
+// render `*bold*` and `\n` in incoming-webhook text.
+pub const DEFAULT_TEMPLATE: &str = "*{ticket}*: {event}\n\
+{consensus}\n\
+Votes: {distribution} ({votes_cast} of {total_voters} voted, avg {average}, median {median})\n\
+Final estimate: *{estimate}*";
+
+// Incoming-webhook body understood by both Slack and Mattermost.
+#[derive(Debug, Serialize)]
+pub struct ChatMessage {
+    pub text: String,
+}
+
+// Posts reveal results and final estimates to a team channel.
+pub struct ChatNotifier {
+    poster: Poster,
+    url: String,
+    template: String,
+}
+
+impl ChatNotifier {
+    // `None` unless a chat webhook URL is configured.
+    pub fn from_settings(settings: &Settings, log: Arc<DeliveryLog>) -> Option<Self> {
+        let url = settings.chat_webhook_url.clone()?;
+        let template = match &settings.chat_template_path {
+            Some(path) => match std::fs::read_to_string(path) {
+                Ok(template) => template,
+                Err(e) => {
+                    tracing::warn!(path = %path.display(), error = %e, "cannot read chat template, using the default");
+                    DEFAULT_TEMPLATE.to_string()
+                }
+            },
+            None => DEFAULT_TEMPLATE.to_string(),
+        };
+        Some(Self {
+            poster: Poster::new(settings.webhook_max_attempts, log),
+            url,
+            template,
+        })
+    }
+
+    pub fn spawn(self, events: broadcast::Receiver<RoundEvent>) -> tokio::task::JoinHandle<()> {
+        forward_events(events, move |event| {
+            // Nothing worth posting until there are votes to show
+            if event.event == RoundEventKind::RoundStarted {
+                return;
+            }
+            let message = ChatMessage { text: render(&self.template, &event) };
+            let body = serde_json::to_vec(&message).unwrap();
+            let poster = self.poster.clone();
+            let url = self.url.clone();
+            tokio::spawn(async move {
+                poster.deliver(&url, event.event, &body, None).await;
+            });
+        })
+    }
+}
+
+// Fill `{placeholder}`s in `template`; unknown ones are left as written.
+// Available: ticket, event, votes, distribution, consensus, average, median,
+// min, max, votes_cast, total_voters, estimate.
+pub fn render(template: &str, event: &RoundEvent) -> String {
+    let stats = &event.statistics;
+    let dash = || "-".to_string();
+    let fields: [(&str, String); 12] = [
+        ("ticket", event.issue_number.clone().unwrap_or_else(|| "(no ticket)".to_string())),
+        ("event", match event.event {
+            RoundEventKind::RoundStarted => "Voting started",
+            RoundEventKind::VotesRevealed => "Votes revealed",
+            RoundEventKind::EstimateSet => "Final estimate set",
+        }.to_string()),
+        ("votes", votes_list(event)),
+        ("distribution", distribution(event)),
+        ("consensus", consensus(event)),
+        ("average", stats.average.map(|a| format!("{:.1}", a)).unwrap_or_else(dash)),
+        ("median", stats.median.map(|m| format!("{}", m)).unwrap_or_else(dash)),
+        ("min", stats.min.map(|m| m.to_string()).unwrap_or_else(dash)),
+        ("max", stats.max.map(|m| m.to_string()).unwrap_or_else(dash)),
+        ("votes_cast", stats.votes_cast.to_string()),
+        ("total_voters", stats.total_voters.to_string()),
+        ("estimate", event.final_estimate.map(|e| e.to_string()).unwrap_or_else(|| "not set yet".to_string())),
+    ];
+
+    let mut out = template.to_string();
+    for (name, value) in fields {
+        out = out.replace(&format!("{{{}}}", name), &value);
+    }
+    out
+}
+
+// One "name: vote" line per player, sorted by name.
+fn votes_list(event: &RoundEvent) -> String {
+    let mut votes: Vec<_> = event.votes.iter().collect();
+    votes.sort_by(|a, b| a.player_name.cmp(&b.player_name));
+    votes
+        .iter()
+        .map(|v| match v.vote {
+            Some(vote) => format!("• {}: {}", v.player_name, vote),
+            None => format!("• {}: -", v.player_name),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+// "3 ×2, 5 ×1", lowest card first.
+fn distribution(event: &RoundEvent) -> String {
+    let mut counts: BTreeMap<u32, usize> = BTreeMap::new();
+    for vote in event.votes.iter().filter_map(|v| v.vote) {
+        *counts.entry(vote).or_insert(0) += 1;
+    }
+    if counts.is_empty() {
+        return "none".to_string();
+    }
+    counts
+        .iter()
+        .map(|(card, count)| format!("{} ×{}", card, count))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn consensus(event: &RoundEvent) -> String {
+    match (event.statistics.min, event.statistics.max) {
+        (Some(min), Some(max)) if min == max => format!("Consensus on {}", min),
+        (Some(min), Some(max)) => format!("No consensus, votes range from {} to {}", min, max),
+        _ => "No votes cast".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http_api::{calculate_statistics, VoteDetail};
+    use crate::metrics::Metrics;
+    use crate::room::RoomHandle;
+    use crate::state::ServerState;
+    use crate::test_support::{start_receiver, temp_settings, wait_for, Receiver};
+    use common::AdminCommand;
+
+    fn revealed(votes: &[(&str, Option<u32>)], estimate: Option<u32>) -> RoundEvent {
+        let cast: Vec<u32> = votes.iter().filter_map(|(_, v)| *v).collect();
+        RoundEvent {
+            event: if estimate.is_some() { RoundEventKind::EstimateSet } else { RoundEventKind::VotesRevealed },
+            timestamp_unix: 0,
+            issue_number: Some("PROJ-7".to_string()),
+            ticket: Some(common::Ticket::new("PROJ-7")),
+            votes: votes
+                .iter()
+                .map(|(name, vote)| VoteDetail { player_name: name.to_string(), vote: *vote })
+                .collect(),
+            statistics: calculate_statistics(&cast, votes.len()),
+            final_estimate: estimate,
+        }
+    }
+
+    #[test]
+    fn default_template_summarises_the_round() {
+        let event = revealed(&[("Bob", Some(5)), ("Alice", Some(3)), ("Carol", Some(5)), ("Dan", None)], None);
+        assert_eq!(
+            render(DEFAULT_TEMPLATE, &event),
+            "*PROJ-7*: Votes revealed\n\
+             No consensus, votes range from 3 to 5\n\
+             Votes: 3 ×1, 5 ×2 (3 of 4 voted, avg 4.3, median 5)\n\
+             Final estimate: *not set yet*"
+        );
+    }
+
+    #[test]
+    fn custom_template_placeholders() {
+        let event = revealed(&[("Bob", Some(8)), ("Alice", Some(8))], Some(8));
+        let template = "{event} for {ticket} -> {estimate} ({consensus})\n{votes}\n{unknown}";
+        assert_eq!(
+            render(template, &event),
+            "Final estimate set for PROJ-7 -> 8 (Consensus on 8)\n• Alice: 8\n• Bob: 8\n{unknown}"
+        );
+    }
+
+    #[test]
+    fn empty_round_renders_placeholders_as_dashes() {
+        let event = revealed(&[("Alice", None)], None);
+        assert_eq!(render("{distribution} {average} {min}-{max} {consensus}", &event), "none - --- No votes cast");
+    }
+
+    #[tokio::test]
+    async fn posts_reveal_and_estimate_to_chat_endpoint() {
+        let receiver = Arc::new(Receiver::default());
+        let url = start_receiver(receiver.clone()).await;
+        let log = Arc::new(DeliveryLog::default());
+
+        let settings = Settings { chat_webhook_url: Some(url), ..temp_settings() };
+        let (status_tx, _) = broadcast::channel(16);
+        let room = RoomHandle::spawn(ServerState::new(), settings.clone(), Arc::new(Metrics::new()), status_tx);
+        ChatNotifier::from_settings(&settings, log.clone()).unwrap().spawn(room.subscribe());
+
+        room.start_voting("test".into(), Some(common::Ticket::new("PROJ-9"))).await.unwrap();
+        room.reveal("test".into()).await.unwrap().unwrap();
+        room.admin("test".into(), AdminCommand::SetEstimate { value: Some(3) }).await.unwrap().unwrap();
+
+        // The round start isn't posted
+        wait_for(|| log.recent().len() == 2).await;
+        let mut texts: Vec<String> = receiver.bodies().iter().map(|b| b["text"].as_str().unwrap().to_string()).collect();
+        texts.sort();
+        assert!(texts[0].starts_with("*PROJ-9*: Final estimate set"), "{}", texts[0]);
+        assert!(texts[0].ends_with("Final estimate: *3*"), "{}", texts[0]);
+        assert!(texts[1].starts_with("*PROJ-9*: Votes revealed"), "{}", texts[1]);
+    }
+}
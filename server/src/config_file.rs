@@ -0,0 +1,23 @@
+use common::VotingConfig;
+use std::fs;
+use std::path::Path;
+
+// The deck and default timeout, kept across restarts. A missing or unreadable
+// file falls back to the defaults, which are written out for editing.
+pub fn load(path: &Path) -> VotingConfig {
+    if let Ok(content) = fs::read_to_string(path) {
+        match serde_json::from_str(&content) {
+            Ok(cfg) => return cfg,
+            Err(e) => tracing::warn!(path = %path.display(), error = %e, "ignoring unreadable config"),
+        }
+    }
+    let default = VotingConfig::default();
+    if let Err(e) = save(path, &default) {
+        tracing::warn!(path = %path.display(), error = %e, "failed to write default config");
+    }
+    default
+}
+
+pub fn save(path: &Path, cfg: &VotingConfig) -> std::io::Result<()> {
+    fs::write(path, serde_json::to_string_pretty(cfg)?)
+}
@@ -0,0 +1,60 @@
+use common::Ticket;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::http_api::{calculate_statistics, VoteDetail, VoteStatistics};
+use crate::state::ServerState;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RoundEventKind {
+    RoundStarted,
+    VotesRevealed,
+    EstimateSet,
+}
+
+impl RoundEventKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::RoundStarted => "round_started",
+            Self::VotesRevealed => "votes_revealed",
+            Self::EstimateSet => "estimate_set",
+        }
+    }
+}
+
+// A milestone in a round, published by the room task for integrations
+// (webhooks, chat notifiers). Same data as the reveal endpoint returns.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct RoundEvent {
+    pub event: RoundEventKind,
+    pub timestamp_unix: u64,
+    pub issue_number: Option<String>,
+    pub ticket: Option<Ticket>,
+    // Empty until the votes are revealed
+    pub votes: Vec<VoteDetail>,
+    pub statistics: VoteStatistics,
+    pub final_estimate: Option<u32>,
+}
+
+impl RoundEvent {
+    pub fn new(event: RoundEventKind, state: &ServerState) -> Self {
+        let game = &state.game_state;
+        let revealed = event != RoundEventKind::RoundStarted;
+        let votes = if revealed { state.vote_details() } else { Vec::new() };
+        let actual_votes: Vec<u32> = if revealed {
+            game.votes.values().filter_map(|v| *v).collect()
+        } else {
+            Vec::new()
+        };
+        Self {
+            event,
+            timestamp_unix: common::current_time_unix(),
+            issue_number: state.issue_number(),
+            ticket: game.current_ticket.clone(),
+            votes,
+            statistics: calculate_statistics(&actual_votes, game.players.len()),
+            final_estimate: game.final_estimate,
+        }
+    }
+}
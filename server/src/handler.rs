@@ -1,112 +1,278 @@
-use common::{ClientPayload, ServerPayload, Phase, AdminCommand, Player, Role, current_time_unix};
-use crate::state::{SharedState, Tx};
+use common::{current_time_unix, ClientPayload, ErrorCode, ServerPayload, Phase, AdminCommand, Player, Presence, Role, StateEvent};
+use crate::state::{server_info, ServerState, Tx};
+use crate::validation::{self, Rejection};
 use uuid::Uuid;
 
-pub async fn handle_message(player_id: Uuid, payload: ClientPayload, state: &SharedState, tx: &Tx) {
-    let mut broadcast_needed = false;
-    
-    // Lock state only for short duration
-    {
-        let mut locked_state = state.lock().unwrap();
-        
-        match payload {
-            ClientPayload::Login { name, role, color, symbol } => {
-                // Add player
-                let player = Player {
-                    id: player_id,
-                    name,
-                    role,
-                    position: (2, 2), // Top-left start pos
-                    color,
-                    symbol,
-                    confirmed: false,
-                };
-                locked_state.game_state.players.insert(player_id, player);
-                locked_state.add_client(player_id, tx.clone());
-                
-                // Send Welcome
-                let _ = tx.send(ServerPayload::Welcome {
-                    self_id: player_id,
-                    state: locked_state.game_state.clone(),
-                });
-                
-                broadcast_needed = true;
-            },
-            ClientPayload::Move { x, y } => {
-                if let Some(player) = locked_state.game_state.players.get_mut(&player_id) {
-                    player.position = (x, y);
-                    
-                    // Check auto-reveal condition: Everyone in area?
-                    // Simplified: if voting, just update pos. Visibility handled by client logic effectively 
-                    // (although we decided server sends everything).
-                    
-                    broadcast_needed = true;
-                }
-            },
-            ClientPayload::Vote { value } => {
-                // Check if voting is active
-                if let Phase::Voting { .. } = locked_state.game_state.phase {
-                     locked_state.game_state.votes.insert(player_id, value);
-                     broadcast_needed = true;
-                }
-            },
-            ClientPayload::VoteConfirm { confirmed } => {
-                if let Some(player) = locked_state.game_state.players.get_mut(&player_id) {
-                    player.confirmed = confirmed;
-                    broadcast_needed = true;
-                }
-            },
-            ClientPayload::Admin(cmd) => {
-                // Verify admin (optional, for now trust role)
-                let is_admin = locked_state.game_state.players.get(&player_id)
-                    .map(|p| p.role == Role::ScrumMaster)
-                    .unwrap_or(false);
-                
-                if is_admin {
-                    match cmd {
-                        AdminCommand::StartVote { ticket, timeout } => {
-                            locked_state.game_state.phase = Phase::Voting {
-                                start_time_unix: current_time_unix(),
-                                duration_secs: timeout,
-                            };
-                            locked_state.game_state.current_ticket = ticket;
-                            locked_state.game_state.votes.clear();
-                            for p in locked_state.game_state.players.values_mut() {
-                                p.confirmed = false;
-                            }
-                            broadcast_needed = true;
-                        },
-                        AdminCommand::Reveal => {
-                            locked_state.game_state.phase = Phase::Revealed;
-                            broadcast_needed = true;
-                        },
-                        AdminCommand::Reset => {
-                             locked_state.game_state.phase = Phase::Idle;
-                             locked_state.game_state.votes.clear();
-                             locked_state.game_state.current_ticket = None;
-                             // Reset positions?
-                             for p in locked_state.game_state.players.values_mut() {
-                                 p.position = (10, 10);
-                             }
-                             broadcast_needed = true;
-                        },
-                        AdminCommand::Kick { player_id: target } => {
-                            locked_state.remove_client(target);
-                            broadcast_needed = true;
-                        },
-                        AdminCommand::UpdateConfig(cfg) => {
-                            locked_state.game_state.config = cfg.clone();
-                            // Save to disk
-                            let _ = std::fs::write("config.json", serde_json::to_string_pretty(&cfg).unwrap());
-                            broadcast_needed = true;
-                        }
-                    }
-                }
+// Runs on the room task, which owns the state; deltas are flushed by the caller.
+// Rejected messages leave the state untouched and get an error back.
+pub fn handle_message(player_id: Uuid, payload: ClientPayload, state: &mut ServerState, tx: &Tx) -> Result<(), Rejection> {
+    let kind = payload.kind();
+    let result = process(player_id, payload, state, tx);
+    if let Err(rejection) = &result {
+        tracing::debug!(%player_id, kind, code = ?rejection.code, "rejected message");
+        let _ = tx.send(rejection.clone().into_payload());
+    }
+    result
+}
+
+fn process(player_id: Uuid, payload: ClientPayload, state: &mut ServerState, tx: &Tx) -> Result<(), Rejection> {
+    let logged_in = state.game_state.players.contains_key(&player_id);
+    if !logged_in && !matches!(payload, ClientPayload::Login { .. } | ClientPayload::Ping { .. }) {
+        return Err(Rejection::new(ErrorCode::NotLoggedIn, "Log in first"));
+    }
+
+    // Heartbeats and resyncs don't count as the player being active
+    if !matches!(payload, ClientPayload::Ping { .. } | ClientPayload::RequestSnapshot) {
+        state.touch(player_id);
+    }
+
+    match payload {
+        ClientPayload::Login { name, role, color, symbol, .. } => {
+            if logged_in {
+                return Err(Rejection::new(ErrorCode::AlreadyLoggedIn, "Already logged in"));
+            }
+            let name = validation::validate_name(&name, &state.game_state)?;
+
+            // Add player
+            let player = Player {
+                id: player_id,
+                name,
+                role,
+                position: state.game_state.map.spawn_point(state.game_state.players.len()),
+                color,
+                symbol,
+                confirmed: false,
+                presence: Presence::Active,
+            };
+            tracing::info!(%player_id, name = %player.name, role = ?player.role, "player joined");
+            state.apply(StateEvent::PlayerJoined(player));
+            state.add_client(player_id, tx.clone());
+            state.touch(player_id);
+
+            // Send Welcome
+            let session_token = state.open_session(player_id);
+            let _ = tx.send(ServerPayload::Welcome {
+                self_id: player_id,
+                state: state.view_for(player_id),
+                session_token,
+                server: server_info(),
+            });
+        },
+        ClientPayload::Move { x, y } => {
+            validation::validate_position(x, y, &state.game_state.map)?;
+            state.apply(StateEvent::PlayerMoved { player_id, position: (x, y) });
+            state.vote_from_position(player_id);
+        },
+        ClientPayload::Vote { value } => {
+            // Check if voting is active
+            if !matches!(state.game_state.phase, Phase::Voting { .. }) {
+                return Err(Rejection::new(ErrorCode::NotVoting, "No vote in progress"));
+            }
+            validation::validate_vote(value, &state.game_state.config)?;
+            state.apply(StateEvent::VoteCast { player_id, value });
+        },
+        ClientPayload::VoteConfirm { confirmed } => {
+            state.apply(StateEvent::PlayerConfirmed { player_id, confirmed });
+            // Someone who was already standing in a zone when the round started votes by
+            // confirming. Elsewhere, keep any vote a scripted client cast with `Vote`.
+            let in_zone = state.game_state.players.get(&player_id)
+                .is_some_and(|p| state.game_state.map.zone_at(p.position.0, p.position.1).is_some());
+            if confirmed && in_zone {
+                state.vote_from_position(player_id);
             }
+        },
+        ClientPayload::Ping { nonce } => {
+            let _ = tx.send(ServerPayload::Pong { nonce });
+        },
+        ClientPayload::RequestSnapshot => {
+            state.send_snapshot(player_id);
+        },
+        ClientPayload::Admin(cmd) => {
+            // Verify admin (optional, for now trust role)
+            let is_admin = state.game_state.players.get(&player_id)
+                .map(|p| p.role == Role::ScrumMaster)
+                .unwrap_or(false);
+            if !is_admin {
+                return Err(Rejection::new(ErrorCode::NotAuthorized, "Only the Scrum Master can do that"));
+            }
+
+            apply_admin(cmd, state)?;
         }
-        
-        if broadcast_needed {
-            locked_state.broadcast_state();
+    }
+    Ok(())
+}
+
+// Admin commands from either front-end; the caller has already checked who is asking.
+pub fn apply_admin(cmd: AdminCommand, state: &mut ServerState) -> Result<(), Rejection> {
+    match cmd {
+        AdminCommand::StartVote { ticket, timeout } => {
+            if let Some(ticket) = &ticket {
+                validation::validate_ticket(ticket)?;
+            }
+            state.start_voting(ticket, timeout);
+        },
+        AdminCommand::Reveal => {
+            state.reveal()?;
+        },
+        AdminCommand::Reset => {
+            state.apply(StateEvent::PhaseChanged { phase: Phase::Idle, ticket: None });
+            state.apply(StateEvent::VotesCleared);
+            // Everyone back to a spawn point, out of the zones
+            let ids: Vec<Uuid> = state.game_state.players.keys().copied().collect();
+            for (i, id) in ids.into_iter().enumerate() {
+                let position = state.game_state.map.spawn_point(i);
+                state.apply(StateEvent::PlayerMoved { player_id: id, position });
+            }
+        },
+        AdminCommand::Kick { player_id: target } => {
+            if !state.game_state.players.contains_key(&target) {
+                return Err(Rejection::new(ErrorCode::UnknownPlayer, "No such player"));
+            }
+            state.kick(target);
+        },
+        AdminCommand::UpdateConfig(cfg) => {
+            validation::validate_config(&cfg)?;
+            // The room task persists the new config
+            state.set_config(cfg);
+        },
+        AdminCommand::SetTimer { timeout } => {
+            let Phase::Voting { .. } = state.game_state.phase else {
+                return Err(Rejection::new(ErrorCode::NotVoting, "No vote in progress"));
+            };
+            validation::validate_timeout(timeout)?;
+            let ticket = state.game_state.current_ticket.clone();
+            state.apply(StateEvent::PhaseChanged {
+                phase: Phase::Voting { start_time_unix: current_time_unix(), duration_secs: timeout },
+                ticket,
+            });
+        },
+        AdminCommand::SetTicket { ticket } => {
+            if let Some(ticket) = &ticket {
+                validation::validate_ticket(ticket)?;
+            }
+            let ticket = ticket.map(|t| state.resolve_ticket(t));
+            let phase = state.game_state.phase.clone();
+            state.apply(StateEvent::PhaseChanged { phase, ticket });
+        },
+        AdminCommand::Revote => {
+            if state.game_state.phase == Phase::Idle {
+                return Err(Rejection::new(ErrorCode::NotVoting, "No round to vote on again"));
+            }
+            let ticket = state.game_state.current_ticket.clone();
+            let timeout = state.game_state.config.default_timeout;
+            state.start_voting(ticket, timeout);
+        },
+        AdminCommand::SetEstimate { value } => {
+            if state.game_state.phase != Phase::Revealed {
+                return Err(Rejection::new(ErrorCode::NotRevealed, "Reveal the votes first"));
+            }
+            state.set_estimate(value);
+        },
+        AdminCommand::SetBacklog { tickets } => {
+            validation::validate_backlog(&tickets)?;
+            state.apply(StateEvent::BacklogChanged(tickets));
+        },
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::{AvatarColor, AvatarSymbol, ServerPayload};
+    use tokio::sync::mpsc;
+
+    fn join(state: &mut ServerState, name: &str) -> (Uuid, Tx, mpsc::UnboundedReceiver<ServerPayload>) {
+        let id = Uuid::new_v4();
+        let (tx, rx) = mpsc::unbounded_channel();
+        let login = ClientPayload::Login {
+            name: name.to_string(),
+            role: Role::Participant,
+            color: AvatarColor::Red,
+            symbol: AvatarSymbol::Human,
+            resume_token: None,
+        };
+        handle_message(id, login, state, &tx).unwrap();
+        (id, tx, rx)
+    }
+
+    #[test]
+    fn moving_into_a_zone_votes_for_its_card() {
+        let mut state = ServerState::new();
+        let (id, tx, _rx) = join(&mut state, "Alice");
+        state.start_voting(None, None);
+
+        let (x, y) = state.game_state.map.zone_for_card(5).unwrap().center();
+        handle_message(id, ClientPayload::Move { x, y }, &mut state, &tx).unwrap();
+        assert_eq!(state.game_state.votes.get(&id), Some(&Some(5)));
+
+        // Stepping out of every zone withdraws the vote
+        let (x, y) = state.game_state.map.spawn_point(0);
+        handle_message(id, ClientPayload::Move { x, y }, &mut state, &tx).unwrap();
+        assert_eq!(state.game_state.votes.get(&id), Some(&None));
+    }
+
+    #[test]
+    fn moves_outside_a_round_do_not_vote() {
+        let mut state = ServerState::new();
+        let (id, tx, _rx) = join(&mut state, "Alice");
+
+        let (x, y) = state.game_state.map.zone_for_card(3).unwrap().center();
+        handle_message(id, ClientPayload::Move { x, y }, &mut state, &tx).unwrap();
+        assert!(state.game_state.votes.is_empty());
+
+        // Confirming once the round is on counts where the player already stands
+        state.start_voting(None, None);
+        handle_message(id, ClientPayload::VoteConfirm { confirmed: true }, &mut state, &tx).unwrap();
+        assert_eq!(state.game_state.votes.get(&id), Some(&Some(3)));
+    }
+
+    #[test]
+    fn confirming_outside_a_zone_keeps_a_cast_vote() {
+        let mut state = ServerState::new();
+        let (id, tx, _rx) = join(&mut state, "Alice");
+        state.start_voting(None, None);
+
+        // Scripted clients vote without walking into a zone
+        handle_message(id, ClientPayload::Vote { value: Some(8) }, &mut state, &tx).unwrap();
+        handle_message(id, ClientPayload::VoteConfirm { confirmed: true }, &mut state, &tx).unwrap();
+        assert_eq!(state.game_state.votes.get(&id), Some(&Some(8)));
+    }
+
+    #[test]
+    fn moves_off_the_map_are_rejected() {
+        let mut state = ServerState::new();
+        let (id, tx, mut rx) = join(&mut state, "Alice");
+        let start = state.game_state.players[&id].position;
+        let width = state.game_state.map.width;
+
+        let err = handle_message(id, ClientPayload::Move { x: width, y: 0 }, &mut state, &tx).unwrap_err();
+        assert_eq!(err.code, ErrorCode::OutOfBounds);
+        assert_eq!(state.game_state.players[&id].position, start);
+        let last = std::iter::from_fn(|| rx.try_recv().ok()).last();
+        assert!(matches!(last, Some(ServerPayload::Error { code: ErrorCode::OutOfBounds, .. })));
+    }
+
+    #[test]
+    fn players_spawn_on_the_map_outside_the_zones() {
+        let mut state = ServerState::new();
+        let (alice, _, _a) = join(&mut state, "Alice");
+        let (bob, _, _b) = join(&mut state, "Bob");
+        let map = &state.game_state.map;
+        for id in [alice, bob] {
+            let (x, y) = state.game_state.players[&id].position;
+            assert!(map.contains(x, y));
+            assert!(map.zone_at(x, y).is_none());
         }
+        assert_ne!(state.game_state.players[&alice].position, state.game_state.players[&bob].position);
+    }
+
+    #[test]
+    fn config_changes_relay_the_map() {
+        let mut state = ServerState::new();
+        let config = common::VotingConfig { cards: vec![1, 2, 4, 8], default_timeout: None };
+        apply_admin(AdminCommand::UpdateConfig(config), &mut state).unwrap();
+        let cards: Vec<u32> = state.game_state.map.zones.iter().map(|z| z.value).collect();
+        assert_eq!(cards, vec![1, 2, 4, 8]);
     }
 }
@@ -1,38 +1,128 @@
 use axum::{
-    extract::State,
-    response::{sse::Event, Sse},
-    routing::{get, post},
+    extract::{rejection::JsonRejection, ConnectInfo, DefaultBodyLimit, FromRequest, FromRequestParts, Query, Request, State},
+    http::{
+        header::{AUTHORIZATION, CONTENT_TYPE},
+        request::Parts,
+        HeaderName, HeaderValue, Method, StatusCode,
+    },
+    middleware::{self, Next},
+    response::{sse::Event, IntoResponse, Response, Sse},
+    routing::{get, post, put},
     Json, Router,
 };
+use common::{AdminCommand, ErrorCode, Ticket, TrackerKind, VotingConfig, MAX_FRAME_BYTES};
 use futures::stream::Stream;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use std::{convert::Infallible, time::Duration};
+use std::{convert::Infallible, net::SocketAddr, time::Duration};
 use tokio_stream::wrappers::BroadcastStream;
 use tokio_stream::StreamExt;
-use tower_http::cors::{Any, CorsLayer};
+use tower_http::cors::{AllowOrigin, CorsLayer};
+use uuid::Uuid;
 
-use crate::state::SharedState;
+use crate::audit::{AuditEntry, AuditQuery, AuditSource};
+use crate::metrics::Metrics;
+use crate::room::{RoomClosed, RoomHandle};
+use crate::validation::{self, Rejection};
+use crate::trackers::{TicketQuery, Trackers};
+use crate::webhooks::{Delivery, DeliveryLog};
 
 // Request/Response types for the HTTP API
 
-#[derive(Debug, Deserialize)]
-pub struct StartVotingRequest {
+// The ticket for `/api/start-voting` and `/api/ticket`. Every field is
+// optional; the browser extension sends the key, summary, description and link
+// it scrapes from the issue page. With neither a key nor a title there is no ticket.
+#[derive(Debug, Default, Deserialize, JsonSchema)]
+pub struct TicketRequest {
+    // Issue key, e.g. "PROJ-1"
     pub issue_number: Option<String>,
+    // Summary; the key stands in for it when missing
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub url: Option<String>,
+    #[serde(default)]
+    pub labels: Vec<String>,
+    pub source: Option<TrackerKind>,
 }
 
-#[derive(Debug, Serialize)]
-pub struct StartVotingResponse {
+impl TicketRequest {
+    fn into_ticket(self) -> Option<Ticket> {
+        let (title, key) = match (self.title, self.issue_number) {
+            (Some(title), key) => (title, key),
+            (None, Some(key)) => (key, None),
+            (None, None) => return None,
+        };
+        Some(Ticket {
+            key,
+            description: self.description,
+            url: self.url,
+            labels: self.labels,
+            source: self.source,
+            ..Ticket::new(title)
+        })
+    }
+}
+
+// Kick by id, or by display name (case-insensitive) when the id isn't handy.
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct KickRequest {
+    pub player_id: Option<Uuid>,
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct SetTimerRequest {
+    // Omit or null for a round without a timer
+    pub timeout_secs: Option<u64>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct SetEstimateRequest {
+    // Omit or null to clear the estimate
+    pub value: Option<u32>,
+}
+
+// Replace the backlog with issues from a configured tracker.
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ImportBacklogRequest {
+    pub source: TrackerKind,
+    #[serde(flatten)]
+    pub query: TicketQuery,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct BacklogResponse {
+    pub success: bool,
+    pub tickets: Vec<Ticket>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct ActionResponse {
     pub success: bool,
     pub message: String,
 }
 
-#[derive(Debug, Serialize)]
+impl ActionResponse {
+    fn ok(message: &str) -> Json<Self> {
+        Json(Self { success: true, message: message.to_string() })
+    }
+}
+
+// Body of every failed API call; `code` matches the TCP protocol's error codes.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct ErrorResponse {
+    pub success: bool,
+    pub code: ErrorCode,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 pub struct VoteDetail {
     pub player_name: String,
     pub vote: Option<u32>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 pub struct RevealVotesResponse {
     pub success: bool,
     pub issue_number: Option<String>,
@@ -40,7 +130,7 @@ pub struct RevealVotesResponse {
     pub statistics: VoteStatistics,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 pub struct VoteStatistics {
     pub total_voters: usize,
     pub votes_cast: usize,
@@ -51,16 +141,29 @@ pub struct VoteStatistics {
     pub mode: Option<u32>,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 pub struct StatusUpdate {
     pub phase: String,
     pub issue_number: Option<String>,
+    pub ticket: Option<Ticket>,
     pub connected_players: Vec<ConnectedPlayer>,
     pub votes_cast: usize,
     pub total_players: usize,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct VersionResponse {
+    pub version: String,
+    pub protocol_version: u32,
+    pub uptime_secs: u64,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct ReadinessResponse {
+    pub ready: bool,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 pub struct ConnectedPlayer {
     pub name: String,
     pub has_voted: bool,
@@ -68,132 +171,434 @@ pub struct ConnectedPlayer {
 
 // Shared state for HTTP API including broadcast channel
 pub struct HttpState {
-    pub game_state: SharedState,
+    pub room: RoomHandle,
+    pub metrics: std::sync::Arc<Metrics>,
+    pub deliveries: std::sync::Arc<DeliveryLog>,
+    pub trackers: Trackers,
     pub status_tx: tokio::sync::broadcast::Sender<StatusUpdate>,
+    pub started_at: std::time::Instant,
+    pub admin_token: Option<String>,
+    pub cors_origins: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct ApiError {
+    status: StatusCode,
+    code: ErrorCode,
+    message: String,
+}
+
+impl ApiError {
+    fn new(status: StatusCode, code: ErrorCode, message: impl Into<String>) -> Self {
+        Self { status, code, message: message.into() }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let body = ErrorResponse { success: false, code: self.code, message: self.message };
+        (self.status, Json(body)).into_response()
+    }
+}
+
+impl From<Rejection> for ApiError {
+    fn from(rejection: Rejection) -> Self {
+        let status = match rejection.code {
+            ErrorCode::MalformedMessage | ErrorCode::FrameTooLarge => StatusCode::BAD_REQUEST,
+            ErrorCode::NotLoggedIn | ErrorCode::NotAuthorized | ErrorCode::Kicked => StatusCode::FORBIDDEN,
+            ErrorCode::UnknownPlayer => StatusCode::NOT_FOUND,
+            ErrorCode::AlreadyLoggedIn | ErrorCode::NameTaken | ErrorCode::NotVoting | ErrorCode::NotRevealed => {
+                StatusCode::CONFLICT
+            }
+            ErrorCode::InvalidName
+            | ErrorCode::InvalidCard
+            | ErrorCode::InvalidConfig
+            | ErrorCode::InvalidTicket
+            | ErrorCode::OutOfBounds => StatusCode::UNPROCESSABLE_ENTITY,
+            ErrorCode::RateLimited => StatusCode::TOO_MANY_REQUESTS,
+            ErrorCode::Unavailable => StatusCode::SERVICE_UNAVAILABLE,
+        };
+        Self::new(status, rejection.code, rejection.message)
+    }
+}
+
+impl From<RoomClosed> for ApiError {
+    fn from(e: RoomClosed) -> Self {
+        Self::new(StatusCode::SERVICE_UNAVAILABLE, ErrorCode::Unavailable, e.to_string())
+    }
+}
+
+impl From<JsonRejection> for ApiError {
+    fn from(rejection: JsonRejection) -> Self {
+        Self::new(rejection.status(), ErrorCode::MalformedMessage, rejection.body_text())
+    }
+}
+
+// `Json` whose rejections use the API's error body instead of plain text.
+pub struct ApiJson<T>(pub T);
+
+impl<T, S> FromRequest<S> for ApiJson<T>
+where
+    Json<T>: FromRequest<S, Rejection = JsonRejection>,
+    S: Send + Sync,
+{
+    type Rejection = ApiError;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let Json(value) = Json::<T>::from_request(req, state).await?;
+        Ok(ApiJson(value))
+    }
+}
+
+// Who is calling, for the audit log: the `X-Poker-Actor` header if the caller
+// identifies itself (the extension does), plus the peer address when known.
+pub struct Actor(pub String);
+
+impl<S: Send + Sync> FromRequestParts<S> for Actor {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let peer = parts
+            .extensions
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(addr)| addr.ip().to_string())
+            .unwrap_or_else(|| "unknown".to_string());
+        let name = parts
+            .headers
+            .get("x-poker-actor")
+            .and_then(|v| v.to_str().ok())
+            .map(str::trim)
+            .filter(|v| !v.is_empty());
+        Ok(Actor(match name {
+            Some(name) => format!("{} (http:{})", name, peer),
+            None => format!("http:{}", peer),
+        }))
+    }
+}
+
+// Gate for the admin endpoints: `Authorization: Bearer <POKER_ADMIN_TOKEN>`.
+// With no token configured they are switched off rather than left open.
+pub struct AdminAuth;
+
+impl FromRequestParts<std::sync::Arc<HttpState>> for AdminAuth {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &std::sync::Arc<HttpState>) -> Result<Self, Self::Rejection> {
+        let refuse = |message: &str| ApiError::new(StatusCode::UNAUTHORIZED, ErrorCode::NotAuthorized, message);
+        let Some(expected) = &state.admin_token else {
+            return Err(refuse("Admin endpoints are disabled until POKER_ADMIN_TOKEN is set"));
+        };
+        let given = parts
+            .headers
+            .get(AUTHORIZATION)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("Bearer "))
+            .map(str::trim);
+        match given {
+            Some(token) if same_token(token.as_bytes(), expected.as_bytes()) => Ok(AdminAuth),
+            _ => Err(refuse("Missing or wrong admin token")),
+        }
+    }
+}
+
+// Compares every byte, so response times don't hint at how much of a guess was right.
+fn same_token(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+// Every call that changes something goes into the audit log with its status,
+// including ones turned away before they reach the room (bad JSON, unknown
+// player). Admin commands also get their own entry from the room.
+async fn audit_requests(
+    State(state): State<std::sync::Arc<HttpState>>,
+    Actor(actor): Actor,
+    request: Request,
+    next: Next,
+) -> Response {
+    if matches!(*request.method(), Method::GET | Method::HEAD | Method::OPTIONS) {
+        return next.run(request).await;
+    }
+    let method = request.method().to_string();
+    let path = request.uri().path().to_string();
+    let response = next.run(request).await;
+
+    let status = response.status();
+    let details = serde_json::json!({ "method": method, "path": path, "status": status.as_u16() });
+    let result = if status.is_success() { Ok(()) } else { Err(status.to_string()) };
+    let _ = state.room.record(AuditEntry::new(actor, AuditSource::Http, "HttpRequest", details, result)).await;
+    response
 }
 
 pub fn create_router(state: std::sync::Arc<HttpState>) -> Router {
+    // Only the configured origins get CORS headers. The browser extension
+    // calls from its background worker, which its host permissions exempt.
+    let origins = state.cors_origins.iter().filter_map(|o| HeaderValue::from_str(o).ok());
     let cors = CorsLayer::new()
-        .allow_origin(Any)
-        .allow_methods(Any)
-        .allow_headers(Any);
+        .allow_origin(AllowOrigin::list(origins))
+        .allow_methods([Method::GET, Method::POST, Method::PUT])
+        .allow_headers([CONTENT_TYPE, AUTHORIZATION, HeaderName::from_static("x-poker-actor")]);
 
     Router::new()
         .route("/api/start-voting", post(start_voting))
         .route("/api/reveal", post(reveal_votes))
+        .route("/api/reset", post(reset))
+        .route("/api/revote", post(revote))
+        .route("/api/kick", post(kick))
+        .route("/api/config", put(update_config))
+        .route("/api/timer", post(set_timer))
+        .route("/api/ticket", post(set_ticket))
+        .route("/api/estimate", post(set_estimate))
+        .route("/api/backlog", put(set_backlog))
+        .route("/api/backlog/import", post(import_backlog))
         .route("/api/status", get(status_stream))
         .route("/api/status-poll", get(status_poll))
+        .route("/api/audit", get(audit_log))
+        .route("/api/webhooks/deliveries", get(webhook_deliveries))
+        .route("/metrics", get(metrics))
+        .route("/healthz", get(healthz))
+        .route("/readyz", get(readyz))
+        .route("/api/version", get(version))
+        .route("/api/openapi.json", get(openapi))
+        .layer(middleware::from_fn_with_state(state.clone(), audit_requests))
+        // Same ceiling as the TCP protocol, so a full backlog fits either way
+        .layer(DefaultBodyLimit::max(MAX_FRAME_BYTES))
         .layer(cors)
         .with_state(state)
 }
 
 async fn start_voting(
     State(state): State<std::sync::Arc<HttpState>>,
-    Json(payload): Json<StartVotingRequest>,
-) -> Json<StartVotingResponse> {
-    let ticket = payload.issue_number.map(|issue| common::Ticket { title: issue });
-
-    {
-        let mut locked_state = state.game_state.lock().unwrap();
+    Actor(actor): Actor,
+    ApiJson(payload): ApiJson<TicketRequest>,
+) -> Result<Json<ActionResponse>, ApiError> {
+    let ticket = payload.into_ticket();
+    if let Some(ticket) = &ticket {
+        validation::validate_ticket(ticket)?;
+    }
 
-        // Start voting phase
-        locked_state.game_state.phase = common::Phase::Voting {
-            start_time_unix: common::current_time_unix(),
-            duration_secs: locked_state.game_state.config.default_timeout,
-        };
-        locked_state.game_state.current_ticket = ticket;
-        locked_state.game_state.votes.clear();
+    // The room task applies the change, notifies CLI clients and SSE subscribers
+    state.room.start_voting(actor, ticket).await?;
 
-        // Reset confirmed status for all players
-        for player in locked_state.game_state.players.values_mut() {
-            player.confirmed = false;
-        }
+    Ok(ActionResponse::ok("Voting started"))
+}
 
-        // Broadcast state update to CLI clients
-        locked_state.broadcast_state();
-    }
+async fn reveal_votes(
+    State(state): State<std::sync::Arc<HttpState>>,
+    Actor(actor): Actor,
+) -> Result<Json<RevealVotesResponse>, ApiError> {
+    let outcome = state.room.reveal(actor).await??;
 
-    // Send status update to SSE subscribers
-    let _ = state.status_tx.send(get_current_status(&state.game_state));
+    // Calculate statistics
+    let statistics = calculate_statistics(&outcome.actual_votes, outcome.total_players);
 
-    Json(StartVotingResponse {
+    Ok(Json(RevealVotesResponse {
         success: true,
-        message: "Voting started".to_string(),
-    })
+        issue_number: outcome.issue_number,
+        votes: outcome.votes,
+        statistics,
+    }))
 }
 
-async fn reveal_votes(
+// The remaining admin commands map one-to-one onto `AdminCommand` and go
+// through the same code path as a Scrum Master's TCP client.
+async fn admin(
+    state: &HttpState,
+    actor: String,
+    cmd: AdminCommand,
+    message: &str,
+) -> Result<Json<ActionResponse>, ApiError> {
+    state.room.admin(actor, cmd).await??;
+    Ok(ActionResponse::ok(message))
+}
+
+async fn reset(
     State(state): State<std::sync::Arc<HttpState>>,
-) -> Json<RevealVotesResponse> {
-    let (votes, issue_number, statistics) = {
-        let mut locked_state = state.game_state.lock().unwrap();
-
-        // Collect vote details
-        let votes: Vec<VoteDetail> = locked_state
-            .game_state
-            .players
-            .iter()
-            .map(|(id, player)| {
-                let vote = locked_state.game_state.votes.get(id).copied().flatten();
-                VoteDetail {
-                    player_name: player.name.clone(),
-                    vote,
-                }
-            })
-            .collect();
+    _: AdminAuth,
+    Actor(actor): Actor,
+) -> Result<Json<ActionResponse>, ApiError> {
+    admin(&state, actor, AdminCommand::Reset, "Round reset").await
+}
 
-        let issue_number = locked_state
-            .game_state
-            .current_ticket
-            .as_ref()
-            .map(|t| t.title.clone());
-
-        // Calculate statistics
-        let actual_votes: Vec<u32> = locked_state
-            .game_state
-            .votes
-            .values()
-            .filter_map(|v| *v)
-            .collect();
+async fn revote(
+    State(state): State<std::sync::Arc<HttpState>>,
+    _: AdminAuth,
+    Actor(actor): Actor,
+) -> Result<Json<ActionResponse>, ApiError> {
+    admin(&state, actor, AdminCommand::Revote, "Voting restarted").await
+}
+
+async fn kick(
+    State(state): State<std::sync::Arc<HttpState>>,
+    _: AdminAuth,
+    Actor(actor): Actor,
+    ApiJson(payload): ApiJson<KickRequest>,
+) -> Result<Json<ActionResponse>, ApiError> {
+    let player_id = match (payload.player_id, payload.name) {
+        (Some(id), _) => id,
+        (None, Some(name)) => state
+            .room
+            .find_player(name.clone())
+            .await?
+            .ok_or_else(|| Rejection::new(ErrorCode::UnknownPlayer, format!("No player named '{}'", name)))?,
+        (None, None) => {
+            return Err(Rejection::new(ErrorCode::MalformedMessage, "Give a player_id or a name").into());
+        }
+    };
+    admin(&state, actor, AdminCommand::Kick { player_id }, "Player kicked").await
+}
+
+async fn update_config(
+    State(state): State<std::sync::Arc<HttpState>>,
+    _: AdminAuth,
+    Actor(actor): Actor,
+    ApiJson(config): ApiJson<VotingConfig>,
+) -> Result<Json<ActionResponse>, ApiError> {
+    admin(&state, actor, AdminCommand::UpdateConfig(config), "Config updated").await
+}
 
-        let statistics = calculate_statistics(&actual_votes, locked_state.game_state.players.len());
+async fn set_timer(
+    State(state): State<std::sync::Arc<HttpState>>,
+    _: AdminAuth,
+    Actor(actor): Actor,
+    ApiJson(payload): ApiJson<SetTimerRequest>,
+) -> Result<Json<ActionResponse>, ApiError> {
+    let cmd = AdminCommand::SetTimer { timeout: payload.timeout_secs };
+    admin(&state, actor, cmd, "Timer set").await
+}
+
+async fn set_ticket(
+    State(state): State<std::sync::Arc<HttpState>>,
+    _: AdminAuth,
+    Actor(actor): Actor,
+    ApiJson(payload): ApiJson<TicketRequest>,
+) -> Result<Json<ActionResponse>, ApiError> {
+    let ticket = payload.into_ticket();
+    admin(&state, actor, AdminCommand::SetTicket { ticket }, "Ticket set").await
+}
 
-        // Set phase to Revealed
-        locked_state.game_state.phase = common::Phase::Revealed;
+async fn set_estimate(
+    State(state): State<std::sync::Arc<HttpState>>,
+    _: AdminAuth,
+    Actor(actor): Actor,
+    ApiJson(payload): ApiJson<SetEstimateRequest>,
+) -> Result<Json<ActionResponse>, ApiError> {
+    let cmd = AdminCommand::SetEstimate { value: payload.value };
+    admin(&state, actor, cmd, "Estimate set").await
+}
 
-        // Broadcast state update to CLI clients
-        locked_state.broadcast_state();
+async fn set_backlog(
+    State(state): State<std::sync::Arc<HttpState>>,
+    _: AdminAuth,
+    Actor(actor): Actor,
+    ApiJson(tickets): ApiJson<Vec<Ticket>>,
+) -> Result<Json<ActionResponse>, ApiError> {
+    admin(&state, actor, AdminCommand::SetBacklog { tickets }, "Backlog set").await
+}
 
-        (votes, issue_number, statistics)
+async fn import_backlog(
+    State(state): State<std::sync::Arc<HttpState>>,
+    _: AdminAuth,
+    Actor(actor): Actor,
+    ApiJson(payload): ApiJson<ImportBacklogRequest>,
+) -> Result<Json<BacklogResponse>, ApiError> {
+    let Some(source) = state.trackers.get(payload.source) else {
+        let message = format!("No {:?} tracker is configured", payload.source);
+        return Err(Rejection::new(ErrorCode::InvalidConfig, message).into());
     };
+    if payload.query.numbers.is_empty() && payload.query.label.is_none() {
+        return Err(Rejection::new(ErrorCode::MalformedMessage, "Give issue numbers, a label, or both").into());
+    }
 
-    // Send status update to SSE subscribers
-    let _ = state.status_tx.send(get_current_status(&state.game_state));
+    let tickets = source.fetch(&payload.query).await.map_err(|e| {
+        tracing::warn!(source = ?payload.source, error = %e, "backlog import failed");
+        ApiError::new(StatusCode::BAD_GATEWAY, ErrorCode::Unavailable, e.to_string())
+    })?;
+    state
+        .room
+        .admin(actor, AdminCommand::SetBacklog { tickets: tickets.clone() })
+        .await??;
+    Ok(Json(BacklogResponse { success: true, tickets }))
+}
 
-    Json(RevealVotesResponse {
-        success: true,
-        issue_number,
-        votes,
-        statistics,
+// Recent webhook deliveries, oldest first.
+async fn webhook_deliveries(
+    State(state): State<std::sync::Arc<HttpState>>,
+    _: AdminAuth,
+) -> Json<Vec<Delivery>> {
+    Json(state.deliveries.recent())
+}
+
+// Admin actions and HTTP API calls, oldest first. Filters: actor, command, since, limit.
+async fn audit_log(
+    State(state): State<std::sync::Arc<HttpState>>,
+    _: AdminAuth,
+    Query(query): Query<AuditQuery>,
+) -> Result<Json<Vec<AuditEntry>>, ApiError> {
+    Ok(Json(state.room.audit(query).await?))
+}
+
+// Liveness: the process is up and serving HTTP.
+async fn healthz() -> &'static str {
+    "ok"
+}
+
+// How long the room task gets to answer before we report not ready.
+const READINESS_TIMEOUT: Duration = Duration::from_secs(1);
+
+// Readiness: the room task is running and answering commands.
+async fn readyz(
+    State(state): State<std::sync::Arc<HttpState>>,
+) -> (StatusCode, Json<ReadinessResponse>) {
+    let ready = matches!(
+        tokio::time::timeout(READINESS_TIMEOUT, state.room.status()).await,
+        Ok(Ok(_))
+    );
+    let code = if ready { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+    (code, Json(ReadinessResponse { ready }))
+}
+
+async fn version(
+    State(state): State<std::sync::Arc<HttpState>>,
+) -> Json<VersionResponse> {
+    let info = crate::state::server_info();
+    Json(VersionResponse {
+        version: info.version,
+        protocol_version: info.protocol_version,
+        uptime_secs: state.started_at.elapsed().as_secs(),
     })
 }
 
+async fn openapi() -> Json<serde_json::Value> {
+    Json(crate::openapi::document())
+}
+
+// Prometheus scrape endpoint
+async fn metrics(
+    State(state): State<std::sync::Arc<HttpState>>,
+) -> ([(axum::http::header::HeaderName, &'static str); 1], String) {
+    (
+        [(axum::http::header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        state.metrics.render(),
+    )
+}
+
 // Simple polling endpoint for Chrome extension (avoids CORS issues with SSE)
 async fn status_poll(
     State(state): State<std::sync::Arc<HttpState>>,
-) -> Json<StatusUpdate> {
-    Json(get_current_status(&state.game_state))
+) -> Result<Json<StatusUpdate>, ApiError> {
+    Ok(Json(state.room.status().await?))
 }
 
 async fn status_stream(
     State(state): State<std::sync::Arc<HttpState>>,
-) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
-    // Send initial status immediately
-    let initial_status = get_current_status(&state.game_state);
-
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, ApiError> {
+    // Subscribe before asking for the initial status so no change slips in between
     let rx = state.status_tx.subscribe();
     let stream = BroadcastStream::new(rx);
 
+    // Send initial status immediately
+    let initial_status = state.room.status().await?;
+
     // Create a stream that first sends initial status, then listens for updates
     let initial_stream = futures::stream::once(async move {
         Ok::<_, Infallible>(Event::default().json_data(initial_status).unwrap())
@@ -206,55 +611,14 @@ async fn status_stream(
 
     let combined_stream = initial_stream.chain(update_stream);
 
-    Sse::new(combined_stream).keep_alive(
+    Ok(Sse::new(combined_stream).keep_alive(
         axum::response::sse::KeepAlive::new()
             .interval(Duration::from_secs(15))
             .text("keep-alive"),
-    )
+    ))
 }
 
-fn get_current_status(game_state: &SharedState) -> StatusUpdate {
-    let locked_state = game_state.lock().unwrap();
-
-    let phase = match &locked_state.game_state.phase {
-        common::Phase::Idle => "idle".to_string(),
-        common::Phase::Voting { .. } => "voting".to_string(),
-        common::Phase::Revealed => "revealed".to_string(),
-    };
-
-    let issue_number = locked_state
-        .game_state
-        .current_ticket
-        .as_ref()
-        .map(|t| t.title.clone());
-
-    let connected_players: Vec<ConnectedPlayer> = locked_state
-        .game_state
-        .players
-        .iter()
-        .map(|(_, player)| ConnectedPlayer {
-            name: player.name.clone(),
-            has_voted: player.confirmed,
-        })
-        .collect();
-
-    let votes_cast = locked_state
-        .game_state
-        .votes
-        .values()
-        .filter(|v| v.is_some())
-        .count();
-
-    StatusUpdate {
-        phase,
-        issue_number,
-        connected_players,
-        votes_cast,
-        total_players: locked_state.game_state.players.len(),
-    }
-}
-
-fn calculate_statistics(votes: &[u32], total_players: usize) -> VoteStatistics {
+pub fn calculate_statistics(votes: &[u32], total_players: usize) -> VoteStatistics {
     if votes.is_empty() {
         return VoteStatistics {
             total_voters: total_players,
@@ -273,7 +637,7 @@ fn calculate_statistics(votes: &[u32], total_players: usize) -> VoteStatistics {
     let sum: u32 = sorted_votes.iter().sum();
     let average = Some(sum as f64 / sorted_votes.len() as f64);
 
-    let median = if sorted_votes.len() % 2 == 0 {
+    let median = if sorted_votes.len().is_multiple_of(2) {
         let mid = sorted_votes.len() / 2;
         Some((sorted_votes[mid - 1] + sorted_votes[mid]) as f64 / 2.0)
     } else {
@@ -303,3 +667,492 @@ fn calculate_statistics(votes: &[u32], total_players: usize) -> VoteStatistics {
         mode,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::ServerState;
+    use crate::settings::GithubSettings;
+    use crate::test_support::{fixture, start_recorded, temp_settings, Recorded};
+    use crate::trackers::GithubIssues;
+    use axum::body::Body;
+    use axum::http::{Method, Request};
+    use common::{AvatarColor, AvatarSymbol, ClientPayload, Role};
+    use http_body_util::BodyExt;
+    use std::sync::Arc;
+    use tokio::sync::{broadcast, mpsc};
+    use tower::ServiceExt;
+
+    struct TestApi {
+        router: Router,
+        room: RoomHandle,
+        config_path: std::path::PathBuf,
+    }
+
+    fn setup() -> TestApi {
+        setup_with(Trackers::default())
+    }
+
+    const ADMIN_TOKEN: &str = "s3cret";
+    const EXTENSION_ORIGIN: &str = "chrome-extension://abcdefghijklmnop";
+
+    fn setup_with(trackers: Trackers) -> TestApi {
+        build(trackers, Some(ADMIN_TOKEN.to_string()))
+    }
+
+    fn build(trackers: Trackers, admin_token: Option<String>) -> TestApi {
+        let settings = temp_settings();
+        let config_path = settings.config_path.clone();
+        let metrics = Arc::new(Metrics::new());
+        let (status_tx, _) = broadcast::channel(16);
+        let room = RoomHandle::spawn(ServerState::new(), settings, metrics.clone(), status_tx.clone());
+        let router = create_router(Arc::new(HttpState {
+            room: room.clone(),
+            metrics,
+            deliveries: Arc::new(DeliveryLog::default()),
+            trackers,
+            status_tx,
+            started_at: std::time::Instant::now(),
+            admin_token,
+            cors_origins: vec![EXTENSION_ORIGIN.to_string()],
+        }));
+        TestApi { router, room, config_path }
+    }
+
+    // The caller keeps the receiver alive for as long as the player should stay connected.
+    async fn join(room: &RoomHandle, name: &str) -> (Uuid, mpsc::UnboundedReceiver<common::ServerPayload>) {
+        let id = Uuid::new_v4();
+        let (tx, rx) = mpsc::unbounded_channel();
+        let login = ClientPayload::Login {
+            name: name.to_string(),
+            role: Role::Participant,
+            color: AvatarColor::Red,
+            symbol: AvatarSymbol::Human,
+            resume_token: None,
+        };
+        room.message(id, login, tx).await.unwrap();
+        (id, rx)
+    }
+
+    // Calls as an admin; see `call_as` for other credentials.
+    async fn call(api: &TestApi, method: Method, uri: &str, body: Option<&str>) -> (StatusCode, serde_json::Value) {
+        call_as(api, Some(ADMIN_TOKEN), method, uri, body).await
+    }
+
+    async fn call_as(
+        api: &TestApi,
+        token: Option<&str>,
+        method: Method,
+        uri: &str,
+        body: Option<&str>,
+    ) -> (StatusCode, serde_json::Value) {
+        let mut request = Request::builder().method(method).uri(uri).header("content-type", "application/json");
+        if let Some(token) = token {
+            request = request.header("authorization", format!("Bearer {}", token));
+        }
+        let request = request.body(body.map(|b| Body::from(b.to_string())).unwrap_or_else(Body::empty)).unwrap();
+        let response = api.router.clone().oneshot(request).await.unwrap();
+        let status = response.status();
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        (status, serde_json::from_slice(&bytes).unwrap_or(serde_json::Value::Null))
+    }
+
+    async fn phase(api: &TestApi) -> String {
+        let (_, body) = call(api, Method::GET, "/api/status-poll", None).await;
+        body["phase"].as_str().unwrap().to_string()
+    }
+
+    #[tokio::test]
+    async fn reset_returns_room_to_idle() {
+        let api = setup();
+        call(&api, Method::POST, "/api/start-voting", Some(r#"{"issue_number":"PROJ-1"}"#)).await;
+        assert_eq!(phase(&api).await, "voting");
+
+        let (status, body) = call(&api, Method::POST, "/api/reset", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["success"], true);
+        assert_eq!(phase(&api).await, "idle");
+    }
+
+    #[tokio::test]
+    async fn start_voting_with_ticket_details() {
+        let api = setup();
+        let body = r#"{"issue_number":"PROJ-5","title":"Checkout times out","description":"Seen on large carts.",
+            "url":"https://example.atlassian.net/browse/PROJ-5","labels":["payments"],"source":"jira"}"#;
+        let (status, _) = call(&api, Method::POST, "/api/start-voting", Some(body)).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let (_, body) = call(&api, Method::GET, "/api/status-poll", None).await;
+        assert_eq!(body["issue_number"], "PROJ-5");
+        assert_eq!(body["ticket"]["title"], "Checkout times out");
+        assert_eq!(body["ticket"]["description"], "Seen on large carts.");
+        assert_eq!(body["ticket"]["labels"], serde_json::json!(["payments"]));
+        assert_eq!(body["ticket"]["source"], "jira");
+
+        let long_title = format!(r#"{{"title":"{}"}}"#, "x".repeat(common::MAX_TICKET_TITLE_LEN + 1));
+        let (status, body) = call(&api, Method::POST, "/api/start-voting", Some(&long_title)).await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(body["code"], "InvalidTicket");
+        let long_url = format!(r#"{{"title":"Export","url":"https://x/{}"}}"#, "x".repeat(common::MAX_TICKET_URL_LEN));
+        let (status, body) = call(&api, Method::POST, "/api/start-voting", Some(&long_url)).await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(body["code"], "InvalidTicket");
+    }
+
+    #[tokio::test]
+    async fn revote_restarts_round_on_same_ticket() {
+        let api = setup();
+        let (status, body) = call(&api, Method::POST, "/api/revote", None).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(body["code"], "NotVoting");
+
+        call(&api, Method::POST, "/api/start-voting", Some(r#"{"issue_number":"PROJ-2"}"#)).await;
+        call(&api, Method::POST, "/api/reveal", None).await;
+        assert_eq!(phase(&api).await, "revealed");
+
+        let (status, _) = call(&api, Method::POST, "/api/revote", None).await;
+        assert_eq!(status, StatusCode::OK);
+        let (_, body) = call(&api, Method::GET, "/api/status-poll", None).await;
+        assert_eq!(body["phase"], "voting");
+        assert_eq!(body["issue_number"], "PROJ-2");
+    }
+
+    #[tokio::test]
+    async fn kick_by_id_and_by_name() {
+        let api = setup();
+        let (alice, _alice_rx) = join(&api.room, "Alice").await;
+        let (_, _bob_rx) = join(&api.room, "Bob").await;
+
+        let (status, _) = call(&api, Method::POST, "/api/kick", Some(&format!(r#"{{"player_id":"{}"}}"#, alice))).await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, _) = call(&api, Method::POST, "/api/kick", Some(r#"{"name":"bob"}"#)).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let (_, body) = call(&api, Method::GET, "/api/status-poll", None).await;
+        assert_eq!(body["total_players"], 0);
+    }
+
+    #[tokio::test]
+    async fn kick_reports_unknown_and_missing_target() {
+        let api = setup();
+        let (status, body) = call(&api, Method::POST, "/api/kick", Some(r#"{"name":"nobody"}"#)).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body["success"], false);
+        assert_eq!(body["code"], "UnknownPlayer");
+
+        let (status, body) = call(&api, Method::POST, "/api/kick", Some("{}")).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["code"], "MalformedMessage");
+    }
+
+    #[tokio::test]
+    async fn update_config_validates_and_persists() {
+        let api = setup();
+        let (status, body) = call(&api, Method::PUT, "/api/config", Some(r#"{"cards":[],"default_timeout":null}"#)).await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(body["code"], "InvalidConfig");
+
+        let (status, _) = call(&api, Method::PUT, "/api/config", Some(r#"{"cards":[1,2,3],"default_timeout":45}"#)).await;
+        assert_eq!(status, StatusCode::OK);
+        let saved: VotingConfig = serde_json::from_str(&std::fs::read_to_string(&api.config_path).unwrap()).unwrap();
+        assert_eq!(saved.cards, vec![1, 2, 3]);
+        assert_eq!(saved.default_timeout, Some(45));
+    }
+
+    #[tokio::test]
+    async fn set_timer_requires_running_round() {
+        let api = setup();
+        let (status, body) = call(&api, Method::POST, "/api/timer", Some(r#"{"timeout_secs":60}"#)).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(body["code"], "NotVoting");
+
+        call(&api, Method::POST, "/api/start-voting", Some("{}")).await;
+        let (status, _) = call(&api, Method::POST, "/api/timer", Some(r#"{"timeout_secs":60}"#)).await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, body) = call(&api, Method::POST, "/api/timer", Some(r#"{"timeout_secs":0}"#)).await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(body["code"], "InvalidConfig");
+    }
+
+    #[tokio::test]
+    async fn set_ticket_keeps_phase() {
+        let api = setup();
+        call(&api, Method::POST, "/api/start-voting", Some(r#"{"issue_number":"PROJ-3"}"#)).await;
+        let (status, _) = call(&api, Method::POST, "/api/ticket", Some(r#"{"issue_number":"PROJ-4"}"#)).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let (_, body) = call(&api, Method::GET, "/api/status-poll", None).await;
+        assert_eq!(body["phase"], "voting");
+        assert_eq!(body["issue_number"], "PROJ-4");
+
+        // Same body as start-voting, so the details come along
+        let ticket = r#"{"issue_number":"PROJ-5","title":"Export CSV","description":"All columns","labels":["reports"]}"#;
+        call(&api, Method::POST, "/api/ticket", Some(ticket)).await;
+        let (_, body) = call(&api, Method::GET, "/api/status-poll", None).await;
+        assert_eq!(body["issue_number"], "PROJ-5");
+        assert_eq!(body["ticket"]["title"], "Export CSV");
+        assert_eq!(body["ticket"]["description"], "All columns");
+
+        let (status, body) = call(&api, Method::POST, "/api/ticket", Some(r#"{"title":" "}"#)).await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(body["code"], "InvalidTicket");
+    }
+
+    #[tokio::test]
+    async fn reveal_needs_an_open_round() {
+        let api = setup();
+        let (status, body) = call(&api, Method::POST, "/api/reveal", None).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(body["code"], "NotVoting");
+        assert_eq!(phase(&api).await, "idle");
+    }
+
+    #[tokio::test]
+    async fn set_estimate_requires_reveal() {
+        let api = setup();
+        call(&api, Method::POST, "/api/start-voting", Some("{}")).await;
+        let (status, body) = call(&api, Method::POST, "/api/estimate", Some(r#"{"value":5}"#)).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(body["code"], "NotRevealed");
+
+        call(&api, Method::POST, "/api/reveal", None).await;
+        let (status, _) = call(&api, Method::POST, "/api/estimate", Some(r#"{"value":5}"#)).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn import_backlog_from_github() {
+        let tracker = Arc::new(
+            Recorded::new()
+                .with("GET", "/repos/acme/app/issues/12", 200, fixture("github/issue_12.json"))
+                .with("GET", "/repos/acme/app/issues?state=open&labels=needs-estimate&per_page=100", 200, fixture("github/issues_labelled.json")),
+        );
+        let base = start_recorded(tracker).await;
+        let mut trackers = Trackers::default();
+        trackers.add(Arc::new(GithubIssues::new(&GithubSettings { api_url: base, repo: "acme/app".to_string(), token: None }).unwrap()));
+        let api = setup_with(trackers);
+
+        let (status, body) = call(&api, Method::POST, "/api/backlog/import", Some(r#"{"source":"github","numbers":[12],"label":"needs-estimate"}"#)).await;
+        assert_eq!(status, StatusCode::OK);
+        let keys: Vec<_> = body["tickets"].as_array().unwrap().iter().map(|t| t["key"].as_str().unwrap()).collect();
+        assert_eq!(keys, vec!["acme/app#12", "acme/app#15"]);
+
+        call(&api, Method::POST, "/api/start-voting", Some(r#"{"issue_number":"acme/app#15"}"#)).await;
+        let (_, body) = call(&api, Method::GET, "/api/status-poll", None).await;
+        assert_eq!(body["issue_number"], "acme/app#15");
+    }
+
+    #[tokio::test]
+    async fn import_backlog_errors() {
+        let api = setup();
+        let (status, body) = call(&api, Method::POST, "/api/backlog/import", Some(r#"{"source":"gitlab","label":"backlog"}"#)).await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(body["code"], "InvalidConfig");
+
+        let base = start_recorded(Arc::new(Recorded::new())).await;
+        let mut trackers = Trackers::default();
+        trackers.add(Arc::new(GithubIssues::new(&GithubSettings { api_url: base, repo: "acme/app".to_string(), token: None }).unwrap()));
+        let api = setup_with(trackers);
+        let (status, body) = call(&api, Method::POST, "/api/backlog/import", Some(r#"{"source":"github"}"#)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["code"], "MalformedMessage");
+
+        // The mock tracker knows no issues, so this is a 404 upstream
+        let (status, body) = call(&api, Method::POST, "/api/backlog/import", Some(r#"{"source":"github","numbers":[1]}"#)).await;
+        assert_eq!(status, StatusCode::BAD_GATEWAY);
+        assert_eq!(body["code"], "Unavailable");
+    }
+
+    #[tokio::test]
+    async fn set_backlog_validates() {
+        let api = setup();
+        let (status, _) = call(&api, Method::PUT, "/api/backlog", Some(r#"[{"title":"PROJ-1"},{"title":"PROJ-2"}]"#)).await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, body) = call(&api, Method::PUT, "/api/backlog", Some(r#"[{"title":"  "}]"#)).await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(body["code"], "InvalidTicket");
+    }
+
+    #[tokio::test]
+    async fn malformed_body_gets_json_error() {
+        let api = setup();
+        let (status, body) = call(&api, Method::POST, "/api/timer", Some("not json")).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["success"], false);
+        assert_eq!(body["code"], "MalformedMessage");
+    }
+
+    #[tokio::test]
+    async fn admin_calls_are_audited() {
+        let api = setup();
+        call(&api, Method::POST, "/api/reset", None).await;
+        call(&api, Method::POST, "/api/revote", None).await;
+
+        let (_, body) = call(&api, Method::GET, "/api/audit", None).await;
+        let entries: Vec<_> = body.as_array().unwrap().iter().filter(|e| e["command"] != "HttpRequest").collect();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0]["command"], "Reset");
+        assert_eq!(entries[0]["ok"], true);
+        assert_eq!(entries[1]["command"], "Revote");
+        assert_eq!(entries[1]["ok"], false);
+    }
+
+    #[tokio::test]
+    async fn admin_endpoints_need_the_token() {
+        let api = setup();
+        for token in [None, Some("guess")] {
+            let (status, body) = call_as(&api, token, Method::POST, "/api/reset", None).await;
+            assert_eq!(status, StatusCode::UNAUTHORIZED);
+            assert_eq!(body["code"], "NotAuthorized");
+        }
+        let (status, _) = call_as(&api, None, Method::PUT, "/api/backlog", Some("[]")).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        // Webhook URLs and actor names stay private too
+        for uri in ["/api/audit", "/api/webhooks/deliveries"] {
+            for token in [None, Some("guess")] {
+                let (status, _) = call_as(&api, token, Method::GET, uri, None).await;
+                assert_eq!(status, StatusCode::UNAUTHORIZED, "{uri}");
+            }
+            let (status, _) = call_as(&api, Some(ADMIN_TOKEN), Method::GET, uri, None).await;
+            assert_eq!(status, StatusCode::OK, "{uri}");
+        }
+        let (status, _) = call_as(&api, Some(ADMIN_TOKEN), Method::POST, "/api/reset", None).await;
+        assert_eq!(status, StatusCode::OK);
+
+        // What the browser extension uses stays open
+        let (status, _) = call_as(&api, None, Method::POST, "/api/start-voting", Some("{}")).await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, _) = call_as(&api, None, Method::GET, "/api/status-poll", None).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn admin_endpoints_are_off_without_a_configured_token() {
+        let api = build(Trackers::default(), None);
+        let (status, body) = call_as(&api, Some(""), Method::POST, "/api/reset", None).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert!(body["message"].as_str().unwrap().contains("POKER_ADMIN_TOKEN"));
+    }
+
+    #[tokio::test]
+    async fn cors_only_answers_the_configured_origin() {
+        let api = setup();
+        for (origin, allowed) in [(EXTENSION_ORIGIN, true), ("https://evil.example", false)] {
+            let request = Request::builder()
+                .method(Method::OPTIONS)
+                .uri("/api/kick")
+                .header("origin", origin)
+                .header("access-control-request-method", "POST")
+                .header("access-control-request-headers", "authorization")
+                .body(Body::empty())
+                .unwrap();
+            let response = api.router.clone().oneshot(request).await.unwrap();
+            let allow = response.headers().get("access-control-allow-origin");
+            assert_eq!(allow.is_some(), allowed, "{}", origin);
+        }
+    }
+
+    #[tokio::test]
+    async fn http_calls_are_audited_with_their_status() {
+        let api = setup();
+        call(&api, Method::POST, "/api/reset", None).await;
+        call(&api, Method::POST, "/api/timer", Some("not json")).await;
+        call(&api, Method::GET, "/api/status-poll", None).await;
+
+        let (_, body) = call(&api, Method::GET, "/api/audit?command=HttpRequest", None).await;
+        let entries = body.as_array().unwrap();
+        let seen: Vec<_> = entries
+            .iter()
+            .map(|e| {
+                let d = &e["details"];
+                (d["method"].as_str().unwrap(), d["path"].as_str().unwrap(), d["status"].as_u64().unwrap(), e["ok"] == true)
+            })
+            .collect();
+        assert_eq!(seen, vec![("POST", "/api/reset", 200, true), ("POST", "/api/timer", 400, false)]);
+        assert!(entries.iter().all(|e| e["actor"].as_str().unwrap().starts_with("http:")));
+    }
+
+    // Validate a live response against a component of the served OpenAPI document.
+    fn assert_matches_component(doc: &serde_json::Value, component: &str, value: &serde_json::Value) {
+        let schema = serde_json::json!({
+            "$ref": format!("#/components/schemas/{}", component),
+            "components": doc["components"],
+        });
+        let validator = jsonschema::validator_for(&schema).unwrap();
+        let errors: Vec<String> = validator.iter_errors(value).map(|e| e.to_string()).collect();
+        assert!(errors.is_empty(), "{} does not match {}: {:?}", value, component, errors);
+    }
+
+    fn collect_refs<'a>(value: &'a serde_json::Value, refs: &mut Vec<&'a str>) {
+        match value {
+            serde_json::Value::Object(map) => {
+                if let Some(serde_json::Value::String(r)) = map.get("$ref") {
+                    refs.push(r);
+                }
+                map.values().for_each(|v| collect_refs(v, refs));
+            }
+            serde_json::Value::Array(items) => items.iter().for_each(|v| collect_refs(v, refs)),
+            _ => {}
+        }
+    }
+
+    #[tokio::test]
+    async fn openapi_refs_resolve() {
+        let api = setup();
+        let (status, doc) = call(&api, Method::GET, "/api/openapi.json", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(doc["openapi"], "3.1.0");
+
+        let mut refs = Vec::new();
+        collect_refs(&doc, &mut refs);
+        assert!(!refs.is_empty());
+        for r in refs {
+            let pointer = r.strip_prefix('#').expect("only local refs");
+            assert!(doc.pointer(pointer).is_some(), "dangling $ref {}", r);
+        }
+    }
+
+    #[tokio::test]
+    async fn openapi_paths_are_routed() {
+        let api = setup();
+        let doc = crate::openapi::document();
+        for (path, item) in doc["paths"].as_object().unwrap() {
+            for method in item.as_object().unwrap().keys() {
+                let method = Method::from_bytes(method.to_uppercase().as_bytes()).unwrap();
+                let request = Request::builder()
+                    .method(method.clone())
+                    .uri(path)
+                    .header("content-type", "application/json")
+                    .body(Body::from("{}"))
+                    .unwrap();
+                // Only the status matters; the SSE body never ends
+                let status = api.router.clone().oneshot(request).await.unwrap().status();
+                assert_ne!(status, StatusCode::NOT_FOUND, "{} {} is documented but not routed", method, path);
+                assert_ne!(status, StatusCode::METHOD_NOT_ALLOWED, "{} {} is documented but not routed", method, path);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn responses_match_openapi_schemas() {
+        let api = setup();
+        let doc = crate::openapi::document();
+        let (_alice, _alice_rx) = join(&api.room, "Alice").await;
+
+        let (_, body) = call(&api, Method::POST, "/api/start-voting", Some(r#"{"issue_number":"PROJ-5"}"#)).await;
+        assert_matches_component(&doc, "ActionResponse", &body);
+        let (_, body) = call(&api, Method::GET, "/api/status-poll", None).await;
+        assert_matches_component(&doc, "StatusUpdate", &body);
+        let (_, body) = call(&api, Method::POST, "/api/reveal", None).await;
+        assert_matches_component(&doc, "RevealVotesResponse", &body);
+        let (_, body) = call(&api, Method::GET, "/api/version", None).await;
+        assert_matches_component(&doc, "VersionResponse", &body);
+        let (_, body) = call(&api, Method::POST, "/api/timer", Some(r#"{"timeout_secs":5}"#)).await;
+        assert_matches_component(&doc, "ErrorResponse", &body);
+
+        let (_, body) = call(&api, Method::GET, "/api/audit", None).await;
+        for entry in body.as_array().unwrap() {
+            assert_matches_component(&doc, "AuditEntry", entry);
+        }
+    }
+}
@@ -0,0 +1,296 @@
+//! Poker planning server: one room shared by terminal clients over TCP,
+//! plus the HTTP admin API. `main.rs` runs it on the standard ports; tests
+//! start it in-process on ephemeral ones.
+
+use tokio::net::{TcpListener, TcpStream};
+use tokio_util::codec::{Framed, LinesCodec, LinesCodecError};
+use tokio::sync::{mpsc, broadcast};
+use futures::SinkExt;
+use futures::StreamExt;
+use std::sync::Arc;
+use uuid::Uuid;
+use std::net::SocketAddr;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+use common::{ClientPayload, ErrorCode, ServerPayload, MAX_FRAME_BYTES};
+mod state;
+mod audit;
+mod chat;
+mod config_file;
+mod events;
+mod handler;
+mod http_api;
+mod metrics;
+mod openapi;
+mod rate_limit;
+mod room;
+mod settings;
+mod snapshot;
+mod trackers;
+mod validation;
+mod webhooks;
+#[cfg(test)]
+mod test_support;
+use state::ServerState;
+use room::RoomHandle;
+use rate_limit::TokenBucket;
+use validation::Rejection;
+use http_api::HttpState;
+use metrics::Metrics;
+use webhooks::{DeliveryLog, Webhooks};
+use chat::ChatNotifier;
+use trackers::Trackers;
+
+pub use settings::Settings;
+pub use state::HIDDEN_POSITION;
+
+// A started server. Both listeners are bound, so clients can connect as soon
+// as `start` returns.
+pub struct Server {
+    pub tcp_addr: SocketAddr,
+    pub http_addr: SocketAddr,
+    room: RoomHandle,
+    settings: Settings,
+    accept: JoinHandle<std::io::Result<()>>,
+}
+
+// Restore the room, bind both listeners (port 0 picks a free one) and start serving.
+pub async fn start(settings: Settings, tcp_addr: &str, http_addr: &str) -> std::io::Result<Server> {
+    // Pick up where the last run left off, if it saved a snapshot
+    let mut state_val = match snapshot::load(&settings.snapshot_path) {
+        Some(snap) => {
+            tracing::info!(path = %settings.snapshot_path.display(), "restored room from snapshot");
+            ServerState::from_snapshot(snap)
+        }
+        None => ServerState::new(),
+    };
+    state_val.set_config(config_file::load(&settings.config_path));
+
+    // Create broadcast channel for SSE status updates
+    let (status_tx, _) = broadcast::channel::<http_api::StatusUpdate>(100);
+
+    let metrics = Arc::new(Metrics::new());
+
+    // The room task owns the state; everything else talks to it through the handle
+    let room = RoomHandle::spawn(state_val, settings.clone(), metrics.clone(), status_tx.clone());
+
+    let deliveries = Arc::new(DeliveryLog::default());
+    if !settings.webhook_urls.is_empty() {
+        tracing::info!(urls = settings.webhook_urls.len(), signed = settings.webhook_secret.is_some(), "webhooks enabled");
+        Webhooks::new(&settings, deliveries.clone()).spawn(room.subscribe());
+    }
+    if let Some(chat) = ChatNotifier::from_settings(&settings, deliveries.clone()) {
+        tracing::info!("chat notifications enabled");
+        chat.spawn(room.subscribe());
+    }
+    let trackers = Trackers::from_settings(&settings);
+    if !trackers.is_empty() {
+        tracing::info!(github = settings.github.is_some(), gitlab = settings.gitlab.is_some(), "issue trackers enabled");
+        trackers.clone().spawn_write_back(room.subscribe());
+    }
+
+    if settings.admin_token.is_none() {
+        tracing::warn!("POKER_ADMIN_TOKEN is not set, the HTTP admin endpoints will refuse every call");
+    }
+
+    // Create HTTP state
+    let http_state = Arc::new(HttpState {
+        room: room.clone(),
+        metrics: metrics.clone(),
+        deliveries,
+        trackers,
+        status_tx,
+        started_at: std::time::Instant::now(),
+        admin_token: settings.admin_token.clone(),
+        cors_origins: settings.cors_origins.clone(),
+    });
+
+    // Start HTTP server
+    let http_router = http_api::create_router(http_state);
+    let http_listener = TcpListener::bind(http_addr).await?;
+    let http_addr = http_listener.local_addr()?;
+    tracing::info!(addr = %http_addr, "HTTP API listening");
+
+    tokio::spawn(async move {
+        let app = http_router.into_make_service_with_connect_info::<SocketAddr>();
+        axum::serve(http_listener, app).await.unwrap();
+    });
+
+    // Start TCP server for CLI clients
+    let listener = TcpListener::bind(tcp_addr).await?;
+    let tcp_addr = listener.local_addr()?;
+    tracing::info!(addr = %tcp_addr, "TCP server listening");
+    let accept = tokio::spawn(accept_loop(listener, room.clone(), settings.clone(), metrics));
+
+    Ok(Server { tcp_addr, http_addr, room, settings, accept })
+}
+
+impl Server {
+    // Serve until `shutdown` resolves (or the listener fails), then tell the
+    // clients and save the room.
+    pub async fn run_until(self, shutdown: impl std::future::Future<Output = ()>) -> std::io::Result<()> {
+        let result = tokio::select! {
+            result = self.accept => result.unwrap_or_else(|e| Err(std::io::Error::other(e))),
+            _ = shutdown => Ok(()),
+        };
+
+        tracing::info!(path = %self.settings.snapshot_path.display(), "shutting down, saving room");
+        match self.room.shutdown("Server is restarting".to_string()).await {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => tracing::error!(error = %e, "failed to save snapshot"),
+            Err(e) => tracing::error!(error = %e, "failed to stop room"),
+        }
+        // Give connection tasks a moment to flush the shutdown notice
+        tokio::time::sleep(Duration::from_millis(250)).await;
+        result
+    }
+}
+
+async fn accept_loop(
+    listener: TcpListener,
+    room: RoomHandle,
+    settings: Settings,
+    metrics: Arc<Metrics>,
+) -> std::io::Result<()> {
+    loop {
+        let (stream, addr) = listener.accept().await?;
+        let room = room.clone();
+        let settings = settings.clone();
+        let metrics = metrics.clone();
+        tokio::spawn(async move {
+            metrics.client_connected();
+            if let Err(e) = handle_connection(stream, addr, room, settings, &metrics).await {
+                tracing::warn!(peer = %addr, error = %e, "connection ended with error");
+            }
+            metrics.client_disconnected();
+        });
+    }
+}
+
+#[tracing::instrument(name = "conn", skip_all, fields(peer = %addr, player_id = tracing::field::Empty, room = "main"))]
+async fn handle_connection(
+    stream: TcpStream,
+    addr: SocketAddr,
+    room: RoomHandle,
+    settings: Settings,
+    metrics: &Metrics,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let framed = Framed::new(stream, LinesCodec::new_with_max_length(MAX_FRAME_BYTES));
+    let (tx, rx) = mpsc::unbounded_channel();
+    
+    // We need to store the tx in the state, but we don't know the Player ID yet.
+    // Flow: 
+    // 1. Client connects.
+    // 2. Client sends Login.
+    // 3. Server adds to state.
+    // 4. Server sends Welcome + State.
+    
+    tracing::info!("new connection");
+
+    // Wait for Login. Resuming a session swaps in the old player's id.
+    let mut player_id = Uuid::new_v4();
+    tracing::Span::current().record("player_id", tracing::field::display(player_id));
+    
+    // Create a loop to handle outgoing messages (from other parts of the system to this client)
+    let outgoing_rx = rx;
+    let (mut stream_tx, mut stream_rx) = framed.split();
+    
+    // Spawn a task to forward messages from the channel to the TCP stream.
+    // A kick notice is the last thing a player hears; the task hangs up after it.
+    let mut forward_task = tokio::spawn(async move {
+        let mut outgoing_rx = outgoing_rx;
+        while let Some(msg) = outgoing_rx.recv().await {
+            let kicked = matches!(msg, ServerPayload::Error { code: ErrorCode::Kicked, .. });
+            let json = serde_json::to_string(&msg).unwrap();
+            if stream_tx.send(json).await.is_err() {
+                // Client disconnected or error
+                tracing::debug!("send error (disconnected?)");
+                break;
+            }
+            if kicked {
+                let _ = stream_tx.close().await;
+                break;
+            }
+        }
+    });
+
+    let idle_timeout = settings.idle_timeout;
+    let mut bucket = TokenBucket::new(settings.rate_limit_per_sec, settings.rate_limit_burst);
+    // Only report the first dropped message of a burst, not every one
+    let mut throttled = false;
+
+    // Handle incoming messages. Clients ping regularly, so a connection that
+    // stays silent past the idle timeout is treated as dead (e.g. a sleeping laptop).
+    loop {
+        let next = tokio::select! {
+            next = tokio::time::timeout(idle_timeout, stream_rx.next()) => next,
+            // Nothing more can go out, e.g. after a kick
+            _ = &mut forward_task => break,
+        };
+        let result = match next {
+            Ok(Some(result)) => result,
+            Ok(None) => break,
+            Err(_) => {
+                tracing::info!(?idle_timeout, "connection idle, dropping");
+                break;
+            }
+        };
+        match result {
+            Ok(line) => {
+                if !bucket.try_take() {
+                    metrics.message_rate_limited();
+                    if !throttled {
+                        throttled = true;
+                        let _ = tx.send(Rejection::new(ErrorCode::RateLimited, "Too many messages, slow down").into_payload());
+                    }
+                    continue;
+                }
+                throttled = false;
+
+                let clean_line = line.trim();
+                if let Ok(payload) = serde_json::from_str::<ClientPayload>(clean_line) {
+                    metrics.message_received(&payload);
+                    if let ClientPayload::Login { resume_token: Some(token), .. } = &payload {
+                        match room.resume(player_id, *token, tx.clone()).await? {
+                            Ok(Some(resumed_id)) => {
+                                tracing::Span::current().record("player_id", tracing::field::display(resumed_id));
+                                player_id = resumed_id;
+                                continue;
+                            }
+                            Ok(None) => {}
+                            Err(rejection) => {
+                                let _ = tx.send(rejection.into_payload());
+                                continue;
+                            }
+                        }
+                    }
+                    room.message(player_id, payload, tx.clone()).await?;
+                } else {
+                    tracing::warn!(line = clean_line, "failed to parse message");
+                    metrics.parse_failed();
+                    let _ = tx.send(Rejection::new(ErrorCode::MalformedMessage, "Could not parse message").into_payload());
+                }
+            }
+            Err(LinesCodecError::MaxLineLengthExceeded) => {
+                // The framed stream ends after a codec error, so tell the client why and hang up
+                tracing::warn!("oversized frame");
+                let _ = tx.send(Rejection::new(
+                    ErrorCode::FrameTooLarge,
+                    format!("Messages are limited to {} bytes", MAX_FRAME_BYTES),
+                ).into_payload());
+                break;
+            }
+            Err(e) => {
+                tracing::warn!(error = %e, "stream error");
+                break;
+            }
+        }
+    }
+
+    // Cleanup
+    tracing::info!("connection closed");
+    room.disconnect(player_id, tx).await?;
+
+    Ok(())
+}
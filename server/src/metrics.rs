@@ -0,0 +1,172 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
+use std::time::Duration;
+
+use common::ClientPayload;
+
+// Upper bounds (seconds) of the broadcast latency histogram buckets.
+const LATENCY_BUCKETS: [f64; 8] = [0.0001, 0.0005, 0.001, 0.005, 0.01, 0.05, 0.1, 0.5];
+
+// Process-wide counters, shared as `Arc<Metrics>` by the room task, the TCP
+// connections and the HTTP API, and rendered in Prometheus text format.
+pub struct Metrics {
+    connected_clients: AtomicI64,
+    rooms: AtomicI64,
+    rounds_started: AtomicU64,
+    rounds_revealed: AtomicU64,
+    // Keyed by `ClientPayload::kind()`; all keys exist up front so no lock is needed
+    messages: BTreeMap<&'static str, AtomicU64>,
+    parse_failures: AtomicU64,
+    rate_limited: AtomicU64,
+    broadcast_buckets: [AtomicU64; LATENCY_BUCKETS.len()],
+    broadcast_count: AtomicU64,
+    broadcast_sum_micros: AtomicU64,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Self {
+            connected_clients: AtomicI64::new(0),
+            rooms: AtomicI64::new(0),
+            rounds_started: AtomicU64::new(0),
+            rounds_revealed: AtomicU64::new(0),
+            messages: ClientPayload::KINDS.iter().map(|k| (*k, AtomicU64::new(0))).collect(),
+            parse_failures: AtomicU64::new(0),
+            rate_limited: AtomicU64::new(0),
+            broadcast_buckets: Default::default(),
+            broadcast_count: AtomicU64::new(0),
+            broadcast_sum_micros: AtomicU64::new(0),
+        }
+    }
+
+    pub fn client_connected(&self) {
+        self.connected_clients.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn client_disconnected(&self) {
+        self.connected_clients.fetch_sub(1, Ordering::Relaxed);
+    }
+
+    pub fn room_opened(&self) {
+        self.rooms.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn room_closed(&self) {
+        self.rooms.fetch_sub(1, Ordering::Relaxed);
+    }
+
+    pub fn round_started(&self) {
+        self.rounds_started.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn round_revealed(&self) {
+        self.rounds_revealed.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn message_received(&self, payload: &ClientPayload) {
+        if let Some(counter) = self.messages.get(payload.kind()) {
+            counter.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    pub fn parse_failed(&self) {
+        self.parse_failures.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn message_rate_limited(&self) {
+        self.rate_limited.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn observe_broadcast(&self, elapsed: Duration) {
+        let secs = elapsed.as_secs_f64();
+        for (bucket, bound) in self.broadcast_buckets.iter().zip(LATENCY_BUCKETS) {
+            if secs <= bound {
+                bucket.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        self.broadcast_count.fetch_add(1, Ordering::Relaxed);
+        self.broadcast_sum_micros.fetch_add(elapsed.as_micros() as u64, Ordering::Relaxed);
+    }
+
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+
+        gauge(&mut out, "poker_connected_clients", "Open TCP client connections.", self.connected_clients.load(Ordering::Relaxed));
+        gauge(&mut out, "poker_rooms", "Running rooms.", self.rooms.load(Ordering::Relaxed));
+        counter(&mut out, "poker_rounds_started_total", "Voting rounds started.", self.rounds_started.load(Ordering::Relaxed));
+        counter(&mut out, "poker_rounds_revealed_total", "Voting rounds revealed.", self.rounds_revealed.load(Ordering::Relaxed));
+        counter(&mut out, "poker_parse_failures_total", "Client lines that were not valid messages.", self.parse_failures.load(Ordering::Relaxed));
+        counter(&mut out, "poker_rate_limited_total", "Client messages dropped by the rate limiter.", self.rate_limited.load(Ordering::Relaxed));
+
+        let _ = writeln!(out, "# HELP poker_client_messages_total Client messages received, by payload variant.");
+        let _ = writeln!(out, "# TYPE poker_client_messages_total counter");
+        for (kind, count) in &self.messages {
+            let _ = writeln!(out, "poker_client_messages_total{{variant=\"{}\"}} {}", kind, count.load(Ordering::Relaxed));
+        }
+
+        let _ = writeln!(out, "# HELP poker_broadcast_duration_seconds Time to fan a batch of state deltas out to all clients.");
+        let _ = writeln!(out, "# TYPE poker_broadcast_duration_seconds histogram");
+        for (bucket, bound) in self.broadcast_buckets.iter().zip(LATENCY_BUCKETS) {
+            let _ = writeln!(out, "poker_broadcast_duration_seconds_bucket{{le=\"{}\"}} {}", bound, bucket.load(Ordering::Relaxed));
+        }
+        let count = self.broadcast_count.load(Ordering::Relaxed);
+        let _ = writeln!(out, "poker_broadcast_duration_seconds_bucket{{le=\"+Inf\"}} {}", count);
+        let sum = self.broadcast_sum_micros.load(Ordering::Relaxed) as f64 / 1_000_000.0;
+        let _ = writeln!(out, "poker_broadcast_duration_seconds_sum {}", sum);
+        let _ = writeln!(out, "poker_broadcast_duration_seconds_count {}", count);
+
+        out
+    }
+}
+
+fn gauge(out: &mut String, name: &str, help: &str, value: i64) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} gauge", name);
+    let _ = writeln!(out, "{} {}", name, value);
+}
+
+fn counter(out: &mut String, name: &str, help: &str, value: u64) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} counter", name);
+    let _ = writeln!(out, "{} {}", name, value);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn broadcast_latencies_fill_cumulative_buckets() {
+        let metrics = Metrics::new();
+        metrics.observe_broadcast(Duration::from_micros(300));
+        metrics.observe_broadcast(Duration::from_millis(20));
+        metrics.observe_broadcast(Duration::from_secs(2));
+
+        let out = metrics.render();
+        for line in [
+            "poker_broadcast_duration_seconds_bucket{le=\"0.0001\"} 0",
+            "poker_broadcast_duration_seconds_bucket{le=\"0.0005\"} 1",
+            "poker_broadcast_duration_seconds_bucket{le=\"0.01\"} 1",
+            "poker_broadcast_duration_seconds_bucket{le=\"0.05\"} 2",
+            "poker_broadcast_duration_seconds_bucket{le=\"0.5\"} 2",
+            "poker_broadcast_duration_seconds_bucket{le=\"+Inf\"} 3",
+            "poker_broadcast_duration_seconds_sum 2.0203",
+            "poker_broadcast_duration_seconds_count 3",
+        ] {
+            assert!(out.lines().any(|l| l == line), "missing {line:?} in\n{out}");
+        }
+    }
+
+    #[test]
+    fn messages_are_counted_by_variant() {
+        let metrics = Metrics::new();
+        metrics.message_received(&ClientPayload::RequestSnapshot);
+        metrics.message_received(&ClientPayload::RequestSnapshot);
+
+        let out = metrics.render();
+        assert!(out.contains("# TYPE poker_client_messages_total counter"));
+        assert!(out.lines().any(|l| l == "poker_client_messages_total{variant=\"RequestSnapshot\"} 2"));
+        assert!(out.lines().any(|l| l == "poker_client_messages_total{variant=\"Login\"} 0"));
+    }
+}
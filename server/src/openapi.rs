@@ -0,0 +1,189 @@
+use common::{Ticket, VotingConfig};
+use schemars::{generate::SchemaSettings, JsonSchema, SchemaGenerator};
+use serde_json::{json, Value};
+
+use crate::audit::AuditEntry;
+use crate::events::RoundEvent;
+use crate::http_api::{
+    ActionResponse, BacklogResponse, ErrorResponse, ImportBacklogRequest, KickRequest, ReadinessResponse, RevealVotesResponse, SetEstimateRequest,
+    SetTimerRequest, StatusUpdate, TicketRequest, VersionResponse,
+};
+use crate::webhooks::Delivery;
+
+// OpenAPI 3.1 description of the HTTP API, served at `/api/openapi.json`.
+// Schemas come from the same types the handlers serialize, so they can't drift;
+// the paths are listed by hand and checked against the router by the tests.
+pub fn document() -> Value {
+    let mut gen = SchemaSettings::draft2020_12()
+        .with(|s| s.definitions_path = "/components/schemas".into())
+        .into_generator();
+
+    let action = ok(schema::<ActionResponse>(&mut gen));
+    let paths = json!({
+        "/api/start-voting": {
+            "post": op("Start a voting round", Some(schema::<TicketRequest>(&mut gen)), action.clone()),
+        },
+        "/api/reveal": {
+            "post": op("Reveal the votes and return statistics; 409 unless a round is open", None, ok(schema::<RevealVotesResponse>(&mut gen))),
+        },
+        "/api/reset": {
+            "post": admin_only(op("Return to idle, clearing votes and the ticket", None, action.clone())),
+        },
+        "/api/revote": {
+            "post": admin_only(op("Start a fresh round on the current ticket", None, action.clone())),
+        },
+        "/api/kick": {
+            "post": admin_only(op("Remove a player by id or display name", Some(schema::<KickRequest>(&mut gen)), action.clone())),
+        },
+        "/api/config": {
+            "put": admin_only(op("Replace the card deck and default timeout", Some(schema::<VotingConfig>(&mut gen)), action.clone())),
+        },
+        "/api/timer": {
+            "post": admin_only(op("Restart the running round's countdown", Some(schema::<SetTimerRequest>(&mut gen)), action.clone())),
+        },
+        "/api/ticket": {
+            "post": admin_only(op("Change the ticket without restarting the round", Some(schema::<TicketRequest>(&mut gen)), action.clone())),
+        },
+        "/api/estimate": {
+            "post": admin_only(op("Record the agreed estimate after the reveal", Some(schema::<SetEstimateRequest>(&mut gen)), action.clone())),
+        },
+        "/api/backlog": {
+            "put": admin_only(op("Replace the backlog", Some(schema::<Vec<Ticket>>(&mut gen)), action)),
+        },
+        "/api/backlog/import": {
+            "post": admin_only(op(
+                "Replace the backlog with issues from GitHub or GitLab; 502 if the tracker fails",
+                Some(schema::<ImportBacklogRequest>(&mut gen)),
+                ok(schema::<BacklogResponse>(&mut gen)),
+            )),
+        },
+        "/api/webhooks/deliveries": {
+            "get": admin_only(op("Recent webhook deliveries, oldest first", None, ok(schema::<Vec<Delivery>>(&mut gen)))),
+        },
+        "/api/status": {
+            "get": op("Server-sent events, one StatusUpdate per change", None, json!({
+                "description": "Event stream; each event's data is a JSON StatusUpdate",
+                "content": { "text/event-stream": { "schema": schema::<StatusUpdate>(&mut gen) } },
+            })),
+        },
+        "/api/status-poll": {
+            "get": op("Current room status", None, ok(schema::<StatusUpdate>(&mut gen))),
+        },
+        "/api/audit": {
+            "get": admin_only(with_parameters(
+                op("Admin actions and API calls, oldest first", None, ok(schema::<Vec<AuditEntry>>(&mut gen))),
+                json!([
+                    query_param("actor", "Case-insensitive substring of the actor", json!({ "type": "string" })),
+                    query_param("command", "Exact command name, e.g. Reveal", json!({ "type": "string" })),
+                    query_param("since", "Only entries at or after this unix time", json!({ "type": "integer", "minimum": 0 })),
+                    query_param("limit", "Newest N matches (default 100)", json!({ "type": "integer", "minimum": 0 })),
+                ]),
+            )),
+        },
+        "/api/version": {
+            "get": op("Server and protocol version", None, ok(schema::<VersionResponse>(&mut gen))),
+        },
+        "/api/openapi.json": {
+            "get": op("This document", None, ok(json!({ "type": "object" }))),
+        },
+        "/healthz": {
+            "get": op("Liveness", None, text("ok")),
+        },
+        "/readyz": {
+            "get": op("Readiness; 503 when the room isn't answering", None, ok(schema::<ReadinessResponse>(&mut gen))),
+        },
+        "/metrics": {
+            "get": op("Prometheus metrics", None, text("Prometheus text exposition format")),
+        },
+    });
+
+    // Not served by any path; this is what webhook receivers get POSTed
+    schema::<RoundEvent>(&mut gen);
+    let error = schema::<ErrorResponse>(&mut gen);
+    let info = crate::state::server_info();
+    json!({
+        "openapi": "3.1.0",
+        "info": {
+            "title": "Poker Planning HTTP API",
+            "version": info.version,
+            "description": format!("Admin and status API. TCP protocol version {}.", info.protocol_version),
+        },
+        "paths": paths,
+        "components": {
+            "schemas": gen.take_definitions(true),
+            "securitySchemes": {
+                "adminToken": {
+                    "type": "http",
+                    "scheme": "bearer",
+                    "description": "The server's POKER_ADMIN_TOKEN; without one configured these endpoints answer 401",
+                },
+            },
+            "responses": {
+                "Error": {
+                    "description": "The request was refused; `code` says why",
+                    "content": { "application/json": { "schema": error } },
+                },
+            },
+        },
+    })
+}
+
+fn schema<T: JsonSchema>(gen: &mut SchemaGenerator) -> Value {
+    gen.subschema_for::<T>().to_value()
+}
+
+fn op(summary: &str, body: Option<Value>, response: Value) -> Value {
+    let mut op = json!({
+        "summary": summary,
+        "parameters": [
+            {
+                "name": "X-Poker-Actor",
+                "in": "header",
+                "required": false,
+                "description": "Who is calling, recorded in the audit log",
+                "schema": { "type": "string" },
+            },
+        ],
+        "responses": {
+            "200": response,
+            "default": { "$ref": "#/components/responses/Error" },
+        },
+    });
+    if let Some(schema) = body {
+        op["requestBody"] = json!({
+            "required": true,
+            "content": { "application/json": { "schema": schema } },
+        });
+    }
+    op
+}
+
+fn admin_only(mut op: Value) -> Value {
+    op["security"] = json!([{ "adminToken": [] }]);
+    op
+}
+
+fn with_parameters(mut op: Value, extra: Value) -> Value {
+    if let (Some(params), Value::Array(extra)) = (op["parameters"].as_array_mut(), extra) {
+        params.extend(extra);
+    }
+    op
+}
+
+fn query_param(name: &str, description: &str, schema: Value) -> Value {
+    json!({ "name": name, "in": "query", "required": false, "description": description, "schema": schema })
+}
+
+fn ok(schema: Value) -> Value {
+    json!({
+        "description": "OK",
+        "content": { "application/json": { "schema": schema } },
+    })
+}
+
+fn text(description: &str) -> Value {
+    json!({
+        "description": description,
+        "content": { "text/plain": { "schema": { "type": "string" } } },
+    })
+}
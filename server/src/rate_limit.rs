@@ -0,0 +1,77 @@
+use std::time::Instant;
+
+// Classic token bucket: holds up to `capacity` tokens, refilled continuously
+// at `refill_per_sec`. Each message costs one token.
+pub struct TokenBucket {
+    capacity: f64,
+    tokens: f64,
+    refill_per_sec: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    pub fn new(refill_per_sec: u32, capacity: u32) -> Self {
+        Self {
+            capacity: capacity as f64,
+            tokens: capacity as f64,
+            refill_per_sec: refill_per_sec as f64,
+            last_refill: Instant::now(),
+        }
+    }
+
+    pub fn try_take(&mut self) -> bool {
+        self.try_take_at(Instant::now())
+    }
+
+    fn try_take_at(&mut self, now: Instant) -> bool {
+        let elapsed = now.duration_since(self.last_refill).as_secs_f64();
+        self.last_refill = now;
+        self.tokens = (self.tokens + elapsed * self.refill_per_sec).min(self.capacity);
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn take_all(bucket: &mut TokenBucket, now: Instant) -> usize {
+        std::iter::from_fn(|| bucket.try_take_at(now).then_some(())).count()
+    }
+
+    #[test]
+    fn a_full_bucket_allows_one_burst() {
+        let mut bucket = TokenBucket::new(10, 5);
+        let now = bucket.last_refill;
+        assert_eq!(take_all(&mut bucket, now), 5);
+        assert!(!bucket.try_take_at(now));
+    }
+
+    #[test]
+    fn tokens_come_back_at_the_refill_rate() {
+        let mut bucket = TokenBucket::new(10, 5);
+        let start = bucket.last_refill;
+        take_all(&mut bucket, start);
+
+        // 10 per second is one every 100ms
+        assert!(!bucket.try_take_at(start + Duration::from_millis(50)));
+        assert!(bucket.try_take_at(start + Duration::from_millis(100)));
+        assert!(!bucket.try_take_at(start + Duration::from_millis(100)));
+        assert_eq!(take_all(&mut bucket, start + Duration::from_millis(400)), 3);
+    }
+
+    #[test]
+    fn refills_stop_at_capacity() {
+        let mut bucket = TokenBucket::new(10, 5);
+        let start = bucket.last_refill;
+        take_all(&mut bucket, start);
+        assert_eq!(take_all(&mut bucket, start + Duration::from_secs(60)), 5);
+    }
+}
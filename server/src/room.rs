@@ -0,0 +1,335 @@
+use common::{AdminCommand, ClientPayload, ErrorCode, ServerPayload, Ticket};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{broadcast, mpsc, oneshot};
+use uuid::Uuid;
+
+use crate::audit::{AuditEntry, AuditLog, AuditQuery, AuditSource};
+use crate::config_file;
+use crate::events::{RoundEvent, RoundEventKind};
+use crate::handler;
+use crate::http_api::{StatusUpdate, VoteDetail};
+use crate::metrics::Metrics;
+use crate::settings::Settings;
+use crate::snapshot;
+use crate::state::{ServerState, Tx};
+use crate::validation::Rejection;
+
+// Commands accepted by a room task. Front-ends (TCP, HTTP) never touch
+// `ServerState` directly; they send one of these and await the reply.
+pub enum RoomCommand {
+    Message {
+        player_id: Uuid,
+        payload: ClientPayload,
+        tx: Tx,
+    },
+    // Reattach a reconnecting client to its old player; replies with that player's id.
+    // `player_id` is the connection's current one, which must not be logged in yet.
+    Resume {
+        player_id: Uuid,
+        token: Uuid,
+        tx: Tx,
+        reply: oneshot::Sender<Result<Option<Uuid>, Rejection>>,
+    },
+    Disconnect {
+        player_id: Uuid,
+        tx: Tx,
+    },
+    StartVoting {
+        actor: String,
+        ticket: Option<Ticket>,
+        reply: oneshot::Sender<()>,
+    },
+    Reveal {
+        actor: String,
+        reply: oneshot::Sender<Result<RevealOutcome, Rejection>>,
+    },
+    // Any admin command on behalf of an HTTP caller.
+    Admin {
+        actor: String,
+        cmd: AdminCommand,
+        reply: oneshot::Sender<Result<(), Rejection>>,
+    },
+    // Case-insensitive lookup of a player id by display name.
+    FindPlayer {
+        name: String,
+        reply: oneshot::Sender<Option<Uuid>>,
+    },
+    // An entry from outside the room, e.g. an HTTP call and how it went.
+    Record {
+        entry: AuditEntry,
+    },
+    Audit {
+        query: AuditQuery,
+        reply: oneshot::Sender<Vec<AuditEntry>>,
+    },
+    Status {
+        reply: oneshot::Sender<StatusUpdate>,
+    },
+    // Tell clients we're going away, save a snapshot and stop the task.
+    Shutdown {
+        reason: String,
+        reply: oneshot::Sender<std::io::Result<()>>,
+    },
+}
+
+pub struct RevealOutcome {
+    pub issue_number: Option<String>,
+    pub votes: Vec<VoteDetail>,
+    pub actual_votes: Vec<u32>,
+    pub total_players: usize,
+}
+
+#[derive(Debug)]
+pub struct RoomClosed;
+
+impl std::fmt::Display for RoomClosed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "room task is no longer running")
+    }
+}
+
+impl std::error::Error for RoomClosed {}
+
+// Cheap, cloneable handle to a running room task.
+#[derive(Clone)]
+pub struct RoomHandle {
+    tx: mpsc::Sender<RoomCommand>,
+    events: broadcast::Sender<RoundEvent>,
+}
+
+impl RoomHandle {
+    // Spawn the task that owns `state`. Every command that changes the room
+    // flushes its deltas to the clients and publishes one `StatusUpdate`.
+    pub fn spawn(
+        state: ServerState,
+        settings: Settings,
+        metrics: Arc<Metrics>,
+        status_tx: broadcast::Sender<StatusUpdate>,
+    ) -> Self {
+        let (tx, rx) = mpsc::channel(256);
+        let (events, _) = broadcast::channel(64);
+        tokio::spawn(run(state, settings, metrics, rx, status_tx, events.clone()));
+        Self { tx, events }
+    }
+
+    // Round milestones from now on, for integrations.
+    pub fn subscribe(&self) -> broadcast::Receiver<RoundEvent> {
+        self.events.subscribe()
+    }
+
+    pub async fn message(&self, player_id: Uuid, payload: ClientPayload, tx: Tx) -> Result<(), RoomClosed> {
+        self.send(RoomCommand::Message { player_id, payload, tx }).await
+    }
+
+    pub async fn resume(&self, player_id: Uuid, token: Uuid, tx: Tx) -> Result<Result<Option<Uuid>, Rejection>, RoomClosed> {
+        self.request(|reply| RoomCommand::Resume { player_id, token, tx, reply }).await
+    }
+
+    pub async fn disconnect(&self, player_id: Uuid, tx: Tx) -> Result<(), RoomClosed> {
+        self.send(RoomCommand::Disconnect { player_id, tx }).await
+    }
+
+    pub async fn start_voting(&self, actor: String, ticket: Option<Ticket>) -> Result<(), RoomClosed> {
+        self.request(|reply| RoomCommand::StartVoting { actor, ticket, reply }).await
+    }
+
+    pub async fn reveal(&self, actor: String) -> Result<Result<RevealOutcome, Rejection>, RoomClosed> {
+        self.request(|reply| RoomCommand::Reveal { actor, reply }).await
+    }
+
+    pub async fn admin(&self, actor: String, cmd: AdminCommand) -> Result<Result<(), Rejection>, RoomClosed> {
+        self.request(|reply| RoomCommand::Admin { actor, cmd, reply }).await
+    }
+
+    pub async fn find_player(&self, name: String) -> Result<Option<Uuid>, RoomClosed> {
+        self.request(|reply| RoomCommand::FindPlayer { name, reply }).await
+    }
+
+    pub async fn record(&self, entry: AuditEntry) -> Result<(), RoomClosed> {
+        self.send(RoomCommand::Record { entry }).await
+    }
+
+    pub async fn audit(&self, query: AuditQuery) -> Result<Vec<AuditEntry>, RoomClosed> {
+        self.request(|reply| RoomCommand::Audit { query, reply }).await
+    }
+
+    pub async fn status(&self) -> Result<StatusUpdate, RoomClosed> {
+        self.request(|reply| RoomCommand::Status { reply }).await
+    }
+
+    pub async fn shutdown(&self, reason: String) -> Result<std::io::Result<()>, RoomClosed> {
+        self.request(|reply| RoomCommand::Shutdown { reason, reply }).await
+    }
+
+    async fn send(&self, cmd: RoomCommand) -> Result<(), RoomClosed> {
+        self.tx.send(cmd).await.map_err(|_| RoomClosed)
+    }
+
+    async fn request<T>(&self, make: impl FnOnce(oneshot::Sender<T>) -> RoomCommand) -> Result<T, RoomClosed> {
+        let (reply, rx) = oneshot::channel();
+        self.send(make(reply)).await?;
+        rx.await.map_err(|_| RoomClosed)
+    }
+}
+
+// How often the room re-evaluates idle and disconnected players.
+const PRESENCE_SWEEP_INTERVAL: Duration = Duration::from_secs(1);
+
+#[tracing::instrument(name = "room", skip_all, fields(room = "main"))]
+async fn run(
+    mut state: ServerState,
+    settings: Settings,
+    metrics: Arc<Metrics>,
+    mut rx: mpsc::Receiver<RoomCommand>,
+    status_tx: broadcast::Sender<StatusUpdate>,
+    events: broadcast::Sender<RoundEvent>,
+) {
+    metrics.room_opened();
+    let mut audit = AuditLog::open(&settings.audit_path);
+    let mut sweep = tokio::time::interval(PRESENCE_SWEEP_INTERVAL);
+    loop {
+        let cmd = tokio::select! {
+            cmd = rx.recv() => match cmd {
+                Some(cmd) => cmd,
+                None => break,
+            },
+            _ = sweep.tick() => {
+                state.sweep_presence(settings.idle_after, settings.disconnect_grace);
+                flush(&mut state, &metrics, &status_tx);
+                continue;
+            }
+        };
+
+        let config_before = state.game_state.config.clone();
+        match cmd {
+            RoomCommand::Message { player_id, payload, tx } => {
+                // Resolve the actor first; a kick may remove them from the room
+                let admin = match &payload {
+                    ClientPayload::Admin(cmd) => Some((cmd.clone(), actor_name(&state, player_id))),
+                    _ => None,
+                };
+                let result = handler::handle_message(player_id, payload, &mut state, &tx);
+                if let Some((cmd, actor)) = admin {
+                    audit.record(AuditEntry::new(
+                        actor,
+                        AuditSource::Tcp,
+                        cmd.kind(),
+                        serde_json::to_value(&cmd).unwrap_or_default(),
+                        result.map_err(|r| r.message),
+                    ));
+                }
+            }
+            RoomCommand::Resume { player_id, token, tx, reply } => {
+                // Same rule as a plain Login: one player per connection
+                let resumed = if state.game_state.players.contains_key(&player_id) {
+                    Err(Rejection::new(ErrorCode::AlreadyLoggedIn, "Already logged in"))
+                } else {
+                    Ok(state.resume(token, tx))
+                };
+                let _ = reply.send(resumed);
+            }
+            RoomCommand::Disconnect { player_id, tx } => {
+                if state.is_current_client(player_id, &tx) {
+                    state.mark_disconnected(player_id);
+                }
+            }
+            RoomCommand::StartVoting { actor, ticket, reply } => {
+                let details = serde_json::json!({ "ticket": ticket });
+                let timeout = state.game_state.config.default_timeout;
+                state.start_voting(ticket, timeout);
+                audit.record(AuditEntry::new(actor, AuditSource::Http, "StartVote", details, Ok(())));
+                let _ = reply.send(());
+            }
+            RoomCommand::Reveal { actor, reply } => {
+                let outcome = RevealOutcome {
+                    issue_number: state.issue_number(),
+                    votes: state.vote_details(),
+                    actual_votes: state.game_state.votes.values().filter_map(|v| *v).collect(),
+                    total_players: state.game_state.players.len(),
+                };
+                let result = state.reveal().map(|()| outcome);
+                audit.record(AuditEntry::new(
+                    actor,
+                    AuditSource::Http,
+                    "Reveal",
+                    serde_json::Value::Null,
+                    result.as_ref().map(|_| ()).map_err(|r| r.message.clone()),
+                ));
+                let _ = reply.send(result);
+            }
+            RoomCommand::Admin { actor, cmd, reply } => {
+                let kind = cmd.kind();
+                let details = serde_json::to_value(&cmd).unwrap_or_default();
+                let result = handler::apply_admin(cmd, &mut state);
+                audit.record(AuditEntry::new(
+                    actor,
+                    AuditSource::Http,
+                    kind,
+                    details,
+                    result.clone().map_err(|r| r.message),
+                ));
+                let _ = reply.send(result);
+            }
+            RoomCommand::FindPlayer { name, reply } => {
+                let name = name.trim().to_lowercase();
+                let id = state
+                    .game_state
+                    .players
+                    .values()
+                    .find(|p| p.name.to_lowercase() == name)
+                    .map(|p| p.id);
+                let _ = reply.send(id);
+            }
+            RoomCommand::Record { entry } => {
+                audit.record(entry);
+            }
+            RoomCommand::Audit { query, reply } => {
+                let _ = reply.send(audit.query(&query));
+            }
+            RoomCommand::Status { reply } => {
+                let _ = reply.send(state.status());
+            }
+            RoomCommand::Shutdown { reason, reply } => {
+                tracing::info!(%reason, "room shutting down");
+                state.broadcast(ServerPayload::Shutdown { reason });
+                let result = snapshot::save(&settings.snapshot_path, &state.to_snapshot());
+                let _ = reply.send(result);
+                break;
+            }
+        }
+
+        for kind in state.take_round_events() {
+            match kind {
+                RoundEventKind::RoundStarted => metrics.round_started(),
+                RoundEventKind::VotesRevealed => metrics.round_revealed(),
+                RoundEventKind::EstimateSet => {}
+            }
+            let _ = events.send(RoundEvent::new(kind, &state));
+        }
+        if state.game_state.config != config_before {
+            if let Err(e) = config_file::save(&settings.config_path, &state.game_state.config) {
+                tracing::error!(path = %settings.config_path.display(), error = %e, "failed to save config");
+            }
+        }
+        flush(&mut state, &metrics, &status_tx);
+    }
+
+    metrics.room_closed();
+}
+
+// Send pending deltas to the clients and, if anything changed, one status update.
+fn flush(state: &mut ServerState, metrics: &Metrics, status_tx: &broadcast::Sender<StatusUpdate>) {
+    let started = Instant::now();
+    if state.flush_events() {
+        metrics.observe_broadcast(started.elapsed());
+        let _ = status_tx.send(state.status());
+    }
+}
+
+fn actor_name(state: &ServerState, player_id: Uuid) -> String {
+    match state.game_state.players.get(&player_id) {
+        Some(player) => format!("{} ({})", player.name, player_id),
+        None => player_id.to_string(),
+    }
+}
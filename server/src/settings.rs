@@ -0,0 +1,184 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+// Runtime tunables, read from the environment so the npm wrapper and the
+// EC2 box can change them without a rebuild.
+#[derive(Debug, Clone)]
+pub struct Settings {
+    // Drop a connection that sends nothing (not even a ping) for this long.
+    pub idle_timeout: Duration,
+    // Mark a player idle after this long without moving, voting or confirming.
+    pub idle_after: Duration,
+    // Keep a disconnected player in the room this long before removing them.
+    pub disconnect_grace: Duration,
+    // Per-connection token bucket: sustained messages per second and burst size.
+    pub rate_limit_per_sec: u32,
+    pub rate_limit_burst: u32,
+    // Where the room is saved on shutdown and restored from on boot.
+    pub snapshot_path: PathBuf,
+    // Append-only JSON-lines record of admin actions and HTTP API calls.
+    pub audit_path: PathBuf,
+    // Deck and default timeout, rewritten whenever an admin changes them.
+    pub config_path: PathBuf,
+    // Endpoints notified of round events, and the key their payloads are signed with.
+    pub webhook_urls: Vec<String>,
+    pub webhook_secret: Option<String>,
+    // Attempts per delivery, including the first, before it's logged as failed.
+    pub webhook_max_attempts: u32,
+    // Slack/Mattermost incoming webhook for reveal results, and an optional
+    // file with the message template (see `chat::DEFAULT_TEMPLATE`).
+    pub chat_webhook_url: Option<String>,
+    pub chat_template_path: Option<PathBuf>,
+    // Bearer token for the HTTP admin endpoints; they refuse every call while unset.
+    pub admin_token: Option<String>,
+    // Origins browsers may call the HTTP API from, e.g. "chrome-extension://<id>".
+    pub cors_origins: Vec<String>,
+    // Issue trackers tickets can be imported from and estimates written back to.
+    pub github: Option<GithubSettings>,
+    pub gitlab: Option<GitlabSettings>,
+}
+
+#[derive(Debug, Clone)]
+pub struct GithubSettings {
+    pub api_url: String,
+    // "owner/name"
+    pub repo: String,
+    pub token: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GitlabEstimate {
+    // The issue weight field
+    Weight,
+    // A scoped `estimate::<n>` label, which replaces any previous one
+    Label,
+}
+
+#[derive(Debug, Clone)]
+pub struct GitlabSettings {
+    pub url: String,
+    // Numeric id or "group/project" path
+    pub project: String,
+    pub token: Option<String>,
+    pub estimate_as: GitlabEstimate,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            idle_timeout: Duration::from_secs(common::HEARTBEAT_INTERVAL_SECS * 6),
+            idle_after: Duration::from_secs(120),
+            disconnect_grace: Duration::from_secs(30),
+            // Holding an arrow key sends a move and a vote per repeat
+            rate_limit_per_sec: 60,
+            rate_limit_burst: 120,
+            snapshot_path: PathBuf::from("room_snapshot.json"),
+            audit_path: PathBuf::from("audit.log"),
+            config_path: PathBuf::from("config.json"),
+            webhook_urls: Vec::new(),
+            webhook_secret: None,
+            webhook_max_attempts: 5,
+            chat_webhook_url: None,
+            chat_template_path: None,
+            admin_token: None,
+            cors_origins: Vec::new(),
+            github: None,
+            gitlab: None,
+        }
+    }
+}
+
+impl Settings {
+    pub fn from_env() -> Self {
+        let default = Self::default();
+        Self {
+            idle_timeout: env_secs("POKER_IDLE_TIMEOUT_SECS").unwrap_or(default.idle_timeout),
+            idle_after: env_secs("POKER_IDLE_AFTER_SECS").unwrap_or(default.idle_after),
+            disconnect_grace: env_secs("POKER_DISCONNECT_GRACE_SECS").unwrap_or(default.disconnect_grace),
+            rate_limit_per_sec: env_u32("POKER_RATE_LIMIT_PER_SEC").unwrap_or(default.rate_limit_per_sec),
+            rate_limit_burst: env_u32("POKER_RATE_LIMIT_BURST").unwrap_or(default.rate_limit_burst),
+            snapshot_path: std::env::var("POKER_SNAPSHOT_PATH")
+                .map(PathBuf::from)
+                .unwrap_or(default.snapshot_path),
+            audit_path: std::env::var("POKER_AUDIT_LOG_PATH")
+                .map(PathBuf::from)
+                .unwrap_or(default.audit_path),
+            config_path: std::env::var("POKER_CONFIG_PATH")
+                .map(PathBuf::from)
+                .unwrap_or(default.config_path),
+            webhook_urls: env_list("POKER_WEBHOOK_URLS"),
+            webhook_secret: env_string("POKER_WEBHOOK_SECRET"),
+            webhook_max_attempts: env_u32("POKER_WEBHOOK_MAX_ATTEMPTS").unwrap_or(default.webhook_max_attempts),
+            chat_webhook_url: env_string("POKER_CHAT_WEBHOOK_URL"),
+            chat_template_path: std::env::var("POKER_CHAT_TEMPLATE_PATH").ok().map(PathBuf::from),
+            admin_token: env_string("POKER_ADMIN_TOKEN"),
+            cors_origins: env_list("POKER_CORS_ORIGINS"),
+            github: env_string("POKER_GITHUB_REPO").map(|repo| GithubSettings {
+                api_url: env_string("POKER_GITHUB_API_URL").unwrap_or_else(|| "https://api.github.com".to_string()),
+                repo,
+                token: env_string("POKER_GITHUB_TOKEN"),
+            }),
+            gitlab: env_string("POKER_GITLAB_PROJECT").map(|project| GitlabSettings {
+                url: env_string("POKER_GITLAB_URL").unwrap_or_else(|| "https://gitlab.com".to_string()),
+                project,
+                token: env_string("POKER_GITLAB_TOKEN"),
+                estimate_as: match env_string("POKER_GITLAB_ESTIMATE_AS").as_deref() {
+                    Some("label") => GitlabEstimate::Label,
+                    _ => GitlabEstimate::Weight,
+                },
+            }),
+        }
+    }
+}
+
+fn env_secs(key: &str) -> Option<Duration> {
+    std::env::var(key)
+        .ok()
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .filter(|v| *v > 0)
+        .map(Duration::from_secs)
+}
+
+fn env_u32(key: &str) -> Option<u32> {
+    std::env::var(key)
+        .ok()
+        .and_then(|v| v.trim().parse::<u32>().ok())
+        .filter(|v| *v > 0)
+}
+
+// Comma-separated, blanks ignored.
+fn env_list(key: &str) -> Vec<String> {
+    std::env::var(key)
+        .map(|v| v.split(',').map(str::trim).filter(|s| !s.is_empty()).map(String::from).collect())
+        .unwrap_or_default()
+}
+
+// Set and not blank.
+fn env_string(key: &str) -> Option<String> {
+    std::env::var(key).ok().map(|v| v.trim().to_string()).filter(|v| !v.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Each test uses its own variable names; the environment is shared by every test thread.
+    #[test]
+    fn zero_and_garbage_durations_fall_back_to_the_default() {
+        std::env::set_var("POKER_TEST_SECS_ZERO", "0");
+        std::env::set_var("POKER_TEST_SECS_WORD", "soon");
+        std::env::set_var("POKER_TEST_SECS_OK", " 45 ");
+        assert_eq!(env_secs("POKER_TEST_SECS_ZERO"), None);
+        assert_eq!(env_secs("POKER_TEST_SECS_WORD"), None);
+        assert_eq!(env_secs("POKER_TEST_SECS_OK"), Some(Duration::from_secs(45)));
+        assert_eq!(env_secs("POKER_TEST_SECS_UNSET"), None);
+    }
+
+    #[test]
+    fn zero_counts_fall_back_to_the_default() {
+        std::env::set_var("POKER_TEST_U32_ZERO", "0");
+        std::env::set_var("POKER_TEST_U32_OK", "7");
+        assert_eq!(env_u32("POKER_TEST_U32_ZERO"), None);
+        assert_eq!(env_u32("POKER_TEST_U32_OK"), Some(7));
+    }
+}
@@ -0,0 +1,34 @@
+use common::GameState;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use uuid::Uuid;
+
+// What survives a restart: the round in progress and who can resume into it.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RoomSnapshot {
+    pub saved_at_unix: u64,
+    pub game_state: GameState,
+    // Session token -> player id
+    pub sessions: HashMap<Uuid, Uuid>,
+}
+
+pub fn save(path: &Path, snapshot: &RoomSnapshot) -> std::io::Result<()> {
+    let json = serde_json::to_string_pretty(snapshot)?;
+    // Write to a temp file first so a crash mid-write can't leave a truncated snapshot
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, json)?;
+    fs::rename(&tmp, path)
+}
+
+pub fn load(path: &Path) -> Option<RoomSnapshot> {
+    let content = fs::read_to_string(path).ok()?;
+    match serde_json::from_str(&content) {
+        Ok(snapshot) => Some(snapshot),
+        Err(e) => {
+            tracing::warn!(path = %path.display(), error = %e, "ignoring unreadable snapshot");
+            None
+        }
+    }
+}
@@ -1,14 +1,34 @@
-use common::{GameState, ServerPayload, VotingConfig, Phase};
+use common::{ErrorCode, GameState, MapLayout, Presence, ServerInfo, ServerPayload, StateEvent, Ticket, VotingConfig, Phase, current_time_unix, PROTOCOL_VERSION};
 use std::collections::HashMap;
-use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use tokio::sync::mpsc;
 use uuid::Uuid;
 
+use crate::events::RoundEventKind;
+use crate::http_api::{ConnectedPlayer, StatusUpdate, VoteDetail};
+use crate::snapshot::RoomSnapshot;
+use crate::validation::Rejection;
+
 pub type Tx = mpsc::UnboundedSender<ServerPayload>;
 
+// Where other players' avatars are reported while a round is open; outside every zone.
+pub const HIDDEN_POSITION: (u16, u16) = (0, 0);
+
 pub struct ServerState {
     pub game_state: GameState,
     clients: HashMap<Uuid, Tx>,
+    // Deltas applied since the last flush, sent to everyone in one batch.
+    pending_events: Vec<StateEvent>,
+    // Last move/vote/confirm per player, for idle detection.
+    last_activity: HashMap<Uuid, Instant>,
+    // Players whose connection dropped, and when.
+    disconnected_at: HashMap<Uuid, Instant>,
+    // Session token -> player id, for resuming after a reconnect or restart.
+    sessions: HashMap<Uuid, Uuid>,
+    // Round milestones since the last flush, published by the room task.
+    round_events: Vec<RoundEventKind>,
+    // Phase as of the last flush, to spot batches that open or close a round.
+    flushed_phase: Phase,
 }
 
 impl ServerState {
@@ -19,119 +39,441 @@ impl ServerState {
                 phase: Phase::Idle,
                 current_ticket: None,
                 votes: HashMap::new(),
+                final_estimate: None,
+                backlog: Vec::new(),
                 config: VotingConfig::default(),
+                map: MapLayout::default(),
             },
             clients: HashMap::new(),
+            pending_events: Vec::new(),
+            last_activity: HashMap::new(),
+            disconnected_at: HashMap::new(),
+            sessions: HashMap::new(),
+            round_events: Vec::new(),
+            flushed_phase: Phase::Idle,
         }
     }
 
+    // Rebuild a room from a snapshot. Nobody is connected yet, so every
+    // player starts out disconnected and has the grace period to resume.
+    pub fn from_snapshot(snapshot: RoomSnapshot) -> Self {
+        let mut state = Self::new();
+        state.flushed_phase = snapshot.game_state.phase.clone();
+        state.game_state = snapshot.game_state;
+        state.sessions = snapshot.sessions;
+        let now = Instant::now();
+        for player in state.game_state.players.values_mut() {
+            player.presence = Presence::Disconnected;
+            state.disconnected_at.insert(player.id, now);
+        }
+        state
+    }
+
+    pub fn to_snapshot(&self) -> RoomSnapshot {
+        RoomSnapshot {
+            saved_at_unix: current_time_unix(),
+            game_state: self.game_state.clone(),
+            sessions: self.sessions.clone(),
+        }
+    }
+
+    pub fn open_session(&mut self, player_id: Uuid) -> Uuid {
+        let token = Uuid::new_v4();
+        self.sessions.insert(token, player_id);
+        token
+    }
+
+    // Reattach a connection to the player behind `token`. Returns the player's
+    // id, or `None` if the session is unknown or the player has been removed.
+    pub fn resume(&mut self, token: Uuid, tx: Tx) -> Option<Uuid> {
+        let player_id = *self.sessions.get(&token)?;
+        if !self.game_state.players.contains_key(&player_id) {
+            self.sessions.remove(&token);
+            return None;
+        }
+        tracing::info!(%player_id, "player resumed session");
+        self.disconnected_at.remove(&player_id);
+        self.add_client(player_id, tx.clone());
+        self.touch(player_id);
+        let _ = tx.send(ServerPayload::Welcome {
+            self_id: player_id,
+            state: self.view_for(player_id),
+            session_token: token,
+            server: server_info(),
+        });
+        Some(player_id)
+    }
+
+    // True if `tx` is the connection currently attached to `id`. A stale
+    // connection closing must not knock out a player who already resumed.
+    pub fn is_current_client(&self, id: Uuid, tx: &Tx) -> bool {
+        self.clients.get(&id).is_some_and(|current| current.same_channel(tx))
+    }
+
     pub fn add_client(&mut self, id: Uuid, tx: Tx) {
         self.clients.insert(id, tx);
     }
 
     pub fn remove_client(&mut self, id: Uuid) {
         self.clients.remove(&id);
-        self.game_state.players.remove(&id);
-        self.game_state.votes.remove(&id);
+        self.last_activity.remove(&id);
+        self.disconnected_at.remove(&id);
+        self.sessions.retain(|_, player_id| *player_id != id);
+        if self.game_state.players.contains_key(&id) {
+            tracing::info!(player_id = %id, "player left");
+            self.apply(StateEvent::PlayerLeft { player_id: id });
+        }
+    }
+
+    // Removed by the Scrum Master. The player is told why first, and their
+    // connection closes once that notice is out (see `handle_connection`).
+    pub fn kick(&mut self, id: Uuid) {
+        if let Some(tx) = self.clients.get(&id) {
+            let _ = tx.send(ServerPayload::Error {
+                code: ErrorCode::Kicked,
+                message: "You were removed from the room by the Scrum Master".to_string(),
+            });
+        }
+        self.remove_client(id);
+    }
+
+    // Connection closed: keep the player around as `Disconnected` until
+    // `sweep_presence` removes them after the grace period.
+    pub fn mark_disconnected(&mut self, id: Uuid) {
+        self.clients.remove(&id);
+        self.last_activity.remove(&id);
+        if self.game_state.players.contains_key(&id) {
+            tracing::info!(player_id = %id, "player disconnected");
+            self.disconnected_at.insert(id, Instant::now());
+            self.set_presence(id, Presence::Disconnected);
+        }
+    }
+
+    // Record that a player did something; wakes them up if they were idle.
+    pub fn touch(&mut self, id: Uuid) {
+        if !self.clients.contains_key(&id) {
+            return;
+        }
+        self.last_activity.insert(id, Instant::now());
+        self.set_presence(id, Presence::Active);
+    }
+
+    // Called periodically by the room task.
+    pub fn sweep_presence(&mut self, idle_after: Duration, disconnect_grace: Duration) {
+        let now = Instant::now();
+
+        let gone: Vec<Uuid> = self
+            .disconnected_at
+            .iter()
+            .filter(|(_, at)| now.duration_since(**at) >= disconnect_grace)
+            .map(|(id, _)| *id)
+            .collect();
+        for id in gone {
+            self.remove_client(id);
+        }
+
+        let idle: Vec<Uuid> = self
+            .last_activity
+            .iter()
+            .filter(|(_, at)| now.duration_since(**at) >= idle_after)
+            .map(|(id, _)| *id)
+            .collect();
+        for id in idle {
+            self.set_presence(id, Presence::Idle);
+        }
+    }
+
+    fn set_presence(&mut self, id: Uuid, presence: Presence) {
+        let current = self.game_state.players.get(&id).map(|p| p.presence);
+        if current.is_some_and(|p| p != presence) {
+            self.apply(StateEvent::PresenceChanged { player_id: id, presence });
+        }
     }
 
-    #[allow(dead_code)]
     pub fn broadcast(&self, msg: ServerPayload) {
         for tx in self.clients.values() {
             let _ = tx.send(msg.clone());
         }
     }
-    
-    // Helper to broadcast current state to all
-    pub fn broadcast_state(&self) {
-        // We might want to filter sensitive info here if we were strict, 
-        // but for now let's send the whole state and let client hide it usually,
-        // EXCEPT for votes during voting phase.
-        
-        // Actually, the requirements say: "When voting is active, each participant cannot see other participants."
-        // This likely means positions? "Other participants are only revealed when the scrum master ends voting"
-        // And regarding votes: "Each participant... can move to pre-determined 'areas'... When voting is active, each participant cannot see other participants."
-        
-        // So we might need to send personalized views of the state if we want to enforce it server-side.
-        // However, to keep it simple, let's send the full state and mask votes if needed. Only mask votes.
-        // The requirements say: "Each participant cannot see other participants." -> This implies position visibility.
-        // If we want to strictly enforce it, we should filter `players` map in the payload.
-        
-        // Let's implement a 'filter_for' method on GameState or here.
-        
-        for (target_id, tx) in &self.clients {
-            let filtered_state = self.filter_state_for(*target_id);
-            let _ = tx.send(ServerPayload::StateUpdate(filtered_state));
-        }
-    }
-    
-    fn filter_state_for(&self, viewer_id: Uuid) -> GameState {
+
+    // Mutate the game state through a delta and queue it for the next flush.
+    pub fn apply(&mut self, event: StateEvent) {
+        self.game_state.apply(&event);
+        self.pending_events.push(event);
+    }
+
+    // Send queued deltas to all clients, each seeing only what `view_for`
+    // allows. Returns whether anything changed.
+    pub fn flush_events(&mut self) -> bool {
+        if self.pending_events.is_empty() {
+            return false;
+        }
+        let events = std::mem::take(&mut self.pending_events);
+        // A round opening parks everyone else's avatar and one closing fills in
+        // what was withheld, so those batches go out as a fresh view instead
+        let was_voting = matches!(self.flushed_phase, Phase::Voting { .. });
+        self.flushed_phase = self.game_state.phase.clone();
+        if was_voting != matches!(self.game_state.phase, Phase::Voting { .. }) {
+            for &id in self.clients.keys() {
+                self.send_snapshot(id);
+            }
+            return true;
+        }
+        for (&id, tx) in &self.clients {
+            let visible = self.events_for(id, &events);
+            if !visible.is_empty() {
+                let _ = tx.send(ServerPayload::Events(visible));
+            }
+        }
+        true
+    }
+
+    pub fn start_voting(&mut self, ticket: Option<Ticket>, timeout: Option<u64>) {
+        let ticket = ticket.map(|t| self.resolve_ticket(t));
+        self.apply(StateEvent::PhaseChanged {
+            phase: Phase::Voting {
+                start_time_unix: current_time_unix(),
+                duration_secs: timeout,
+            },
+            ticket,
+        });
+        self.apply(StateEvent::VotesCleared);
+        self.round_events.push(RoundEventKind::RoundStarted);
+    }
+
+    // A ticket typed as an imported issue's key ("acme/app#12") becomes that
+    // backlog entry, so its estimate can be written back to the tracker.
+    pub fn resolve_ticket(&self, ticket: Ticket) -> Ticket {
+        if ticket.source.is_some() {
+            return ticket;
+        }
+        let key = ticket.key.as_deref().unwrap_or(&ticket.title).trim();
+        self.game_state
+            .backlog
+            .iter()
+            .find(|t| t.key.as_deref() == Some(key))
+            .cloned()
+            .unwrap_or(ticket)
+    }
+
+    // The map is laid out from the deck, so it changes with the config.
+    pub fn set_config(&mut self, config: VotingConfig) {
+        let map = MapLayout::for_cards(&config.cards);
+        self.apply(StateEvent::ConfigChanged(config));
+        self.apply(StateEvent::MapChanged(map));
+    }
+
+    // During a round, standing in a zone is a vote for its card and standing
+    // anywhere else is no vote.
+    pub fn vote_from_position(&mut self, player_id: Uuid) {
+        if !matches!(self.game_state.phase, Phase::Voting { .. }) {
+            return;
+        }
+        let Some(player) = self.game_state.players.get(&player_id) else {
+            return;
+        };
+        let (x, y) = player.position;
+        let value = self.game_state.map.zone_at(x, y).map(|z| z.value);
+        if self.game_state.votes.get(&player_id).copied().flatten() != value {
+            self.apply(StateEvent::VoteCast { player_id, value });
+        }
+    }
+
+    pub fn reveal(&mut self) -> Result<(), Rejection> {
+        if !matches!(self.game_state.phase, Phase::Voting { .. }) {
+            return Err(Rejection::new(ErrorCode::NotVoting, "No vote in progress"));
+        }
+        self.round_events.push(RoundEventKind::VotesRevealed);
+        let ticket = self.game_state.current_ticket.clone();
+        self.apply(StateEvent::PhaseChanged { phase: Phase::Revealed, ticket });
+        Ok(())
+    }
+
+    pub fn set_estimate(&mut self, value: Option<u32>) {
+        self.apply(StateEvent::EstimateSet { value });
+        if value.is_some() {
+            self.round_events.push(RoundEventKind::EstimateSet);
+        }
+    }
+
+    pub fn take_round_events(&mut self) -> Vec<RoundEventKind> {
+        std::mem::take(&mut self.round_events)
+    }
+
+    // Summary for the SSE/poll endpoints.
+    pub fn status(&self) -> StatusUpdate {
+        let phase = match &self.game_state.phase {
+            Phase::Idle => "idle".to_string(),
+            Phase::Voting { .. } => "voting".to_string(),
+            Phase::Revealed => "revealed".to_string(),
+        };
+
+        let connected_players = self
+            .game_state
+            .players
+            .values()
+            .map(|player| ConnectedPlayer {
+                name: player.name.clone(),
+                has_voted: player.confirmed,
+            })
+            .collect();
+
+        let votes_cast = self
+            .game_state
+            .votes
+            .values()
+            .filter(|v| v.is_some())
+            .count();
+
+        StatusUpdate {
+            phase,
+            issue_number: self.issue_number(),
+            ticket: self.game_state.current_ticket.clone(),
+            connected_players,
+            votes_cast,
+            total_players: self.game_state.players.len(),
+        }
+    }
+
+    pub fn vote_details(&self) -> Vec<VoteDetail> {
+        self.game_state
+            .players
+            .iter()
+            .map(|(id, player)| VoteDetail {
+                player_name: player.name.clone(),
+                vote: self.game_state.votes.get(id).copied().flatten(),
+            })
+            .collect()
+    }
+
+    // The tracker key for imported issues, otherwise whatever was typed.
+    pub fn issue_number(&self) -> Option<String> {
+        self.game_state.current_ticket.as_ref().map(|t| t.key.clone().unwrap_or_else(|| t.title.clone()))
+    }
+
+    // Full snapshot for a single client, e.g. on `RequestSnapshot`.
+    pub fn send_snapshot(&self, target_id: Uuid) {
+        if let Some(tx) = self.clients.get(&target_id) {
+            let _ = tx.send(ServerPayload::StateUpdate(self.view_for(target_id)));
+        }
+    }
+
+    // The state as `viewer_id` may see it. While a round is open, other
+    // players' votes are withheld and their avatars parked at
+    // `HIDDEN_POSITION`, so nobody can tell who stands in which zone.
+    pub fn view_for(&self, viewer_id: Uuid) -> GameState {
         let mut state = self.game_state.clone();
-        
-        // Mask votes if currently voting
         if let Phase::Voting { .. } = state.phase {
-            for (player_id, _vote_opt) in state.votes.iter_mut() {
-                 if *player_id != viewer_id {
-                     // Hide the value, but maybe we want to know IF they voted?
-                     // The requirement says "see statistics" eventually.
-                     // Usually in poker, you see WHO voted, but not WHAT.
-                     // The simple Option<u32> doesn't distinguish "voted X" vs "voted but hidden".
-                     // For now, let's just NOT send the votes of others at all, or we need a way to say "Hidden".
-                     // Since we can't change the type easily without breaking the simple common struct,
-                     // let's just keep the vote if client triggers it, but maybe we need to mask it.
-                     // Wait, `common::GameState` has `votes: HashMap<Uuid, Option<u32>>`.
-                     // If we set it to None, it looks like they didn't vote.
-                     // We might need a separate mechanism or assume the client is honest (not ideal for "cannot see").
-                     // BUT, for the "cannot see other participants" rule:
-                     // This likely means coordinates.
-                     
-                     // "When voting is active, each participant cannot see other participants. Other participants are only revealed when the scrum master ends voting or everyone has gone to a respective area."
-                     
-                     // So we remove players from the map?
-                     // If we remove them, the client thinks they left.
-                     // Maybe we just set their position to a "nowhere" place or don't include them in the `players` map?
-                     // If I remove them from `players` map, the client wont render them. That works.
-                 }
+            state.votes.retain(|id, _| *id == viewer_id);
+            for player in state.players.values_mut().filter(|p| p.id != viewer_id) {
+                player.position = HIDDEN_POSITION;
             }
-             
-             // If voting is active, clear players list except self?
-             // "cannot see other participants"
-             // But we probably want the status bar to show them? "Each active participant will be shown in a status bar for everyone to see."
-             
-             // Contradiction? "Each active participant will be shown in a status bar" vs "cannot see other participants".
-             // Interpretation: Status bar (names/connected) is visible. 2D Avatar (positions) is NOT visible.
-             // So I should mask POSITIONS, not remove the player.
-             // My `Player` struct has `position: (u16, u16)`.
-             // I'll set position to something off-screen or a special value, OR just let the client handle it?
-             // The prompt says: "When voting is active, each participant cannot see other participants... only revealed when..."
-             // I will enforce this server side by setting other players' positions to (0,0) or similar if that's "hidden", 
-             // OR better, I should trust the client for 2D rendering visibility IF the prompt wasn't explicit about "game-like".
-             // Actually, to prevent cheating/peeking, server-side masking is best.
-             // But `Player` struct bundles name, role, and position.
-             // If I modify position to (0,0) they might all stack up at corner.
-             // Let's just pass `position` as is but maybe the client chooses not to render?
-             // "Revealed... when... everyone has gone to a respective area". This implies we CAN see them if everyone voted?
-             // Let's stick to: The SERVER sends the true state. The CLIENT implements the visibility logic for the "2D space". 
-             // Unless "cannot see" implies meaningful information hiding. 
-             // I will leave it to the client for the visuals mainly, but for VOTES, I should mask them.
-             
-             // Masking votes:
-             for (pid, _vote) in state.votes.iter_mut() {
-                 if *pid != viewer_id {
-                     // If they have voted (Some(x)), change it to Some(0) or similar to indicate "Voted but hidden"?
-                     // Or just keep it as is?
-                     // If I zero it out, I lose the information "Voted 5". 
-                     // Common practice: The client knows "Voted" but not value. 
-                     // Use `Some(999)` or something? 
-                     // Or change the `votes` map to only include the user's vote?
-                     // But then we can't show "3/5 voted".
-                     // Okay, let's keep it simple: WE TRUST THE CLIENT for this MVP.
-                     // I will send the full state. The UI will hide it.
-                 }
-             }
         }
         state
     }
+
+    // The deltas `viewer_id` may see from a batch that stays inside or outside a round.
+    fn events_for(&self, viewer_id: Uuid, events: &[StateEvent]) -> Vec<StateEvent> {
+        let voting = matches!(self.game_state.phase, Phase::Voting { .. });
+        events
+            .iter()
+            .filter(|event| match event {
+                StateEvent::PlayerMoved { player_id, .. } | StateEvent::VoteCast { player_id, .. } => {
+                    !voting || *player_id == viewer_id
+                }
+                _ => true,
+            })
+            .cloned()
+            .collect()
+    }
 }
 
-pub type SharedState = Arc<Mutex<ServerState>>;
+pub fn server_info() -> ServerInfo {
+    ServerInfo {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        protocol_version: PROTOCOL_VERSION,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::{AvatarColor, AvatarSymbol, Player, Role};
+
+    fn join(state: &mut ServerState, name: &str) -> (Uuid, mpsc::UnboundedReceiver<ServerPayload>) {
+        let id = Uuid::new_v4();
+        let (tx, rx) = mpsc::unbounded_channel();
+        state.apply(StateEvent::PlayerJoined(Player {
+            id,
+            name: name.to_string(),
+            role: Role::Participant,
+            position: state.game_state.map.spawn_point(state.game_state.players.len()),
+            color: AvatarColor::Red,
+            symbol: AvatarSymbol::Human,
+            confirmed: false,
+            presence: Presence::Active,
+        }));
+        state.add_client(id, tx);
+        (id, rx)
+    }
+
+    fn received(rx: &mut mpsc::UnboundedReceiver<ServerPayload>) -> Vec<ServerPayload> {
+        std::iter::from_fn(|| rx.try_recv().ok()).collect()
+    }
+
+    #[test]
+    fn batches_that_open_or_close_a_round_go_out_as_snapshots() {
+        let mut state = ServerState::new();
+        let (alice, mut alice_rx) = join(&mut state, "Alice");
+        let (_bob, mut bob_rx) = join(&mut state, "Bob");
+        state.flush_events();
+        received(&mut alice_rx);
+        received(&mut bob_rx);
+
+        // Opening the round in the same batch as a move: only fresh views go out
+        state.start_voting(None, None);
+        let zone = state.game_state.map.zone_for_card(5).unwrap().center();
+        state.apply(StateEvent::PlayerMoved { player_id: alice, position: zone });
+        state.flush_events();
+        let seen = received(&mut bob_rx);
+        assert!(matches!(seen.as_slice(), [ServerPayload::StateUpdate(view)] if view.players[&alice].position == HIDDEN_POSITION));
+        assert!(matches!(received(&mut alice_rx).as_slice(), [ServerPayload::StateUpdate(_)]));
+
+        // Inside the round, other players' moves and votes are dropped
+        state.vote_from_position(alice);
+        state.flush_events();
+        assert!(received(&mut bob_rx).is_empty());
+        assert!(matches!(received(&mut alice_rx).as_slice(), [ServerPayload::Events(_)]));
+
+        // Closing it sends the full picture in one message
+        state.reveal().unwrap();
+        state.flush_events();
+        let seen = received(&mut bob_rx);
+        assert!(matches!(seen.as_slice(), [ServerPayload::StateUpdate(view)]
+            if view.votes.get(&alice) == Some(&Some(5)) && view.players[&alice].position == zone));
+    }
+
+    #[test]
+    fn only_an_open_round_can_be_revealed() {
+        let mut state = ServerState::new();
+        let (_alice, mut alice_rx) = join(&mut state, "Alice");
+        state.flush_events();
+        received(&mut alice_rx);
+
+        let refused = state.reveal().unwrap_err();
+        assert_eq!(refused.code, ErrorCode::NotVoting);
+        assert_eq!(state.game_state.phase, Phase::Idle);
+        assert!(state.take_round_events().is_empty());
+        state.flush_events();
+        assert!(received(&mut alice_rx).is_empty());
+
+        state.start_voting(None, None);
+        state.take_round_events();
+        state.reveal().unwrap();
+        assert_eq!(state.take_round_events(), vec![RoundEventKind::VotesRevealed]);
+
+        // Revealing twice doesn't announce it twice
+        assert_eq!(state.reveal().unwrap_err().code, ErrorCode::NotVoting);
+        assert!(state.take_round_events().is_empty());
+    }
+}
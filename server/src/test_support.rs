@@ -0,0 +1,136 @@
+// Helpers shared by the in-module tests.
+
+use axum::{
+    extract::{Request, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    routing::post,
+    Router,
+};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use uuid::Uuid;
+
+use crate::settings::Settings;
+
+// Default settings with every file the room writes under a fresh temp dir.
+pub fn temp_settings() -> Settings {
+    let dir = std::env::temp_dir().join(format!("poker-test-{}", Uuid::new_v4()));
+    std::fs::create_dir_all(&dir).unwrap();
+    Settings {
+        snapshot_path: dir.join("room_snapshot.json"),
+        audit_path: dir.join("audit.log"),
+        config_path: dir.join("config.json"),
+        ..Settings::default()
+    }
+}
+
+// Stand-in for a webhook or chat endpoint: records every request and can be
+// told to fail the first few.
+#[derive(Default)]
+pub struct Receiver {
+    pub fail_first: AtomicU32,
+    pub fail_with: Mutex<Option<StatusCode>>,
+    pub requests: Mutex<Vec<(HeaderMap, Vec<u8>)>>,
+}
+
+impl Receiver {
+    pub fn bodies(&self) -> Vec<serde_json::Value> {
+        self.requests
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(_, body)| serde_json::from_slice(body).unwrap())
+            .collect()
+    }
+}
+
+async fn receive(State(rx): State<Arc<Receiver>>, headers: HeaderMap, body: axum::body::Bytes) -> StatusCode {
+    rx.requests.lock().unwrap().push((headers, body.to_vec()));
+    let remaining = rx.fail_first.load(Ordering::SeqCst);
+    if remaining > 0 {
+        rx.fail_first.store(remaining - 1, Ordering::SeqCst);
+        return rx.fail_with.lock().unwrap().unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+    }
+    StatusCode::OK
+}
+
+// Serves `receiver` on an ephemeral port and returns its URL.
+pub async fn start_receiver(receiver: Arc<Receiver>) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let app = Router::new().route("/hook", post(receive)).with_state(receiver);
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{}/hook", addr)
+}
+
+// Fixture file under `server/tests/fixtures`.
+pub fn fixture(name: &str) -> String {
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures").join(name);
+    std::fs::read_to_string(&path).unwrap_or_else(|e| panic!("cannot read fixture {}: {}", path.display(), e))
+}
+
+#[derive(Debug)]
+pub struct RecordedRequest {
+    pub method: String,
+    // Path and query, as sent
+    pub uri: String,
+    pub headers: HeaderMap,
+    pub body: Vec<u8>,
+}
+
+// Stand-in for a tracker API: answers each `METHOD path?query` with a canned
+// JSON response, 404 for anything else, and records every request.
+#[derive(Default)]
+pub struct Recorded {
+    responses: Vec<(String, String, StatusCode, String)>,
+    pub requests: Mutex<Vec<RecordedRequest>>,
+}
+
+impl Recorded {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with(mut self, method: &str, path_and_query: &str, status: u16, body: String) -> Self {
+        let status = StatusCode::from_u16(status).unwrap();
+        self.responses.push((method.to_string(), path_and_query.to_string(), status, body));
+        self
+    }
+}
+
+async fn replay(State(recorded): State<Arc<Recorded>>, request: Request) -> Response {
+    let (parts, body) = request.into_parts();
+    let body = axum::body::to_bytes(body, usize::MAX).await.unwrap();
+    let method = parts.method.to_string();
+    let uri = parts.uri.path_and_query().map(|p| p.to_string()).unwrap_or_default();
+
+    let canned = recorded
+        .responses
+        .iter()
+        .find(|(m, p, _, _)| *m == method && *p == uri)
+        .map(|(_, _, status, body)| (*status, body.clone()))
+        .unwrap_or((StatusCode::NOT_FOUND, r#"{"message":"Not Found"}"#.to_string()));
+    recorded.requests.lock().unwrap().push(RecordedRequest { method, uri, headers: parts.headers, body: body.to_vec() });
+    (canned.0, [(axum::http::header::CONTENT_TYPE, "application/json")], canned.1).into_response()
+}
+
+// Serves `recorded` on an ephemeral port and returns its base URL.
+pub async fn start_recorded(recorded: Arc<Recorded>) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let app = Router::new().fallback(replay).with_state(recorded);
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{}", addr)
+}
+
+// Poll until `done` holds, for work that happens on spawned tasks.
+pub async fn wait_for(mut done: impl FnMut() -> bool) {
+    for _ in 0..200 {
+        if done() {
+            return;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+    panic!("timed out waiting for background work");
+}
@@ -0,0 +1,247 @@
+use async_trait::async_trait;
+use common::{Ticket, TrackerKind};
+use serde::Deserialize;
+
+use super::{check, http_client, imported, issue_number, merge, TicketQuery, TicketSource, TrackerError, PAGE_SIZE};
+use crate::settings::GithubSettings;
+
+// Estimates are written back as a label, replacing any earlier "estimate: n".
+const ESTIMATE_LABEL_PREFIX: &str = "estimate: ";
+
+#[derive(Debug, Deserialize)]
+struct Issue {
+    number: u64,
+    title: String,
+    // Markdown, or null when empty
+    body: Option<String>,
+    html_url: String,
+    #[serde(default)]
+    labels: Vec<Label>,
+    // Present when the "issue" is really a pull request
+    #[serde(default)]
+    pull_request: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Label {
+    name: String,
+}
+
+// GitHub Issues through the REST API.
+pub struct GithubIssues {
+    client: reqwest::Client,
+    // Always ends in '/', so relative paths join onto it
+    api_url: reqwest::Url,
+    repo: String,
+    token: Option<String>,
+}
+
+impl GithubIssues {
+    pub fn new(settings: &GithubSettings) -> Result<Self, TrackerError> {
+        let invalid = || TrackerError::InvalidUrl(settings.api_url.clone());
+        let mut api_url = reqwest::Url::parse(&settings.api_url).map_err(|_| invalid())?;
+        if !matches!(api_url.scheme(), "http" | "https") || api_url.cannot_be_a_base() {
+            return Err(invalid());
+        }
+        if !api_url.path().ends_with('/') {
+            api_url.set_path(&format!("{}/", api_url.path()));
+        }
+        Ok(Self {
+            client: http_client(),
+            api_url,
+            repo: settings.repo.clone(),
+            token: settings.token.clone(),
+        })
+    }
+
+    fn url(&self, path: &str) -> Result<reqwest::Url, TrackerError> {
+        self.api_url
+            .join(&format!("repos/{}/{}", self.repo, path))
+            .map_err(|_| TrackerError::InvalidUrl(format!("{}repos/{}/{}", self.api_url, self.repo, path)))
+    }
+
+    fn request(&self, method: reqwest::Method, url: reqwest::Url) -> reqwest::RequestBuilder {
+        let mut request = self
+            .client
+            .request(method, url)
+            .header(reqwest::header::ACCEPT, "application/vnd.github+json")
+            .header("X-GitHub-Api-Version", "2022-11-28");
+        if let Some(token) = &self.token {
+            request = request.bearer_auth(token);
+        }
+        request
+    }
+
+    async fn issue(&self, number: u64) -> Result<Issue, TrackerError> {
+        let response = self.request(reqwest::Method::GET, self.url(&format!("issues/{}", number))?).send().await?;
+        Ok(check(response).await?.json().await?)
+    }
+
+    fn ticket(&self, issue: Issue) -> Ticket {
+        imported(
+            TrackerKind::Github,
+            format!("{}#{}", self.repo, issue.number),
+            issue.title,
+            issue.body,
+            issue.html_url,
+            issue.labels.into_iter().map(|l| l.name).collect(),
+        )
+    }
+}
+
+#[async_trait]
+impl TicketSource for GithubIssues {
+    fn kind(&self) -> TrackerKind {
+        TrackerKind::Github
+    }
+
+    async fn fetch(&self, query: &TicketQuery) -> Result<Vec<Ticket>, TrackerError> {
+        let mut by_number = Vec::new();
+        for number in &query.numbers {
+            by_number.push(self.ticket(self.issue(*number).await?));
+        }
+
+        let mut by_label = Vec::new();
+        if let Some(label) = &query.label {
+            let response = self
+                .request(reqwest::Method::GET, self.url("issues")?)
+                .query(&[("state", "open"), ("labels", label.as_str()), ("per_page", PAGE_SIZE)])
+                .send()
+                .await?;
+            let issues: Vec<Issue> = check(response).await?.json().await?;
+            by_label = issues
+                .into_iter()
+                .filter(|issue| issue.pull_request.is_none())
+                .map(|issue| self.ticket(issue))
+                .collect();
+        }
+
+        Ok(merge(by_number, by_label))
+    }
+
+    async fn write_estimate(&self, ticket: &Ticket, estimate: u32) -> Result<(), TrackerError> {
+        let number = issue_number(ticket)?;
+        let wanted = format!("{}{}", ESTIMATE_LABEL_PREFIX, estimate);
+
+        let issue = self.issue(number).await?;
+        let mut already_set = false;
+        for label in issue.labels.iter().filter(|l| l.name.starts_with(ESTIMATE_LABEL_PREFIX)) {
+            if label.name == wanted {
+                already_set = true;
+                continue;
+            }
+            // Label names can contain spaces and slashes, so add them as an encoded segment
+            let mut url = self.url(&format!("issues/{}/labels", number))?;
+            url.path_segments_mut().map_err(|()| TrackerError::InvalidUrl(self.api_url.to_string()))?.push(&label.name);
+            check(self.request(reqwest::Method::DELETE, url).send().await?).await?;
+        }
+
+        if !already_set {
+            let body = serde_json::json!({ "labels": [wanted] });
+            let response = self
+                .request(reqwest::Method::POST, self.url(&format!("issues/{}/labels", number))?)
+                .json(&body)
+                .send()
+                .await?;
+            check(response).await?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{fixture, start_recorded, Recorded};
+    use std::sync::Arc;
+
+    fn github(base: String) -> GithubIssues {
+        GithubIssues::new(&GithubSettings { api_url: base, repo: "acme/app".to_string(), token: Some("ghp_test".to_string()) }).unwrap()
+    }
+
+    #[tokio::test]
+    async fn fetches_by_number_and_label() {
+        let server = Arc::new(
+            Recorded::new()
+                .with("GET", "/repos/acme/app/issues/12", 200, fixture("github/issue_12.json"))
+                .with("GET", "/repos/acme/app/issues?state=open&labels=needs-estimate&per_page=100", 200, fixture("github/issues_labelled.json")),
+        );
+        let base = start_recorded(server.clone()).await;
+
+        let query = TicketQuery { numbers: vec![12], label: Some("needs-estimate".to_string()) };
+        let tickets = github(base).fetch(&query).await.unwrap();
+
+        // #12 appears in both results but is imported once; the pull request is skipped
+        let keys: Vec<_> = tickets.iter().map(|t| t.key.clone().unwrap()).collect();
+        assert_eq!(keys, vec!["acme/app#12", "acme/app#15"]);
+        assert_eq!(tickets[0].title, "Login fails with SSO accounts");
+        assert_eq!(tickets[0].url.as_deref(), Some("https://github.com/acme/app/issues/12"));
+        assert_eq!(tickets[0].labels, vec!["bug", "needs-estimate", "estimate: 3"]);
+        assert!(tickets[0].description.as_deref().unwrap().starts_with("Users signing in"));
+        // An empty body is no description
+        assert_eq!(tickets[1].description, None);
+        assert!(tickets.iter().all(|t| t.source == Some(TrackerKind::Github)));
+
+        let requests = server.requests.lock().unwrap();
+        assert_eq!(requests[0].headers["authorization"], "Bearer ghp_test");
+        assert_eq!(requests[0].headers["accept"], "application/vnd.github+json");
+    }
+
+    #[tokio::test]
+    async fn missing_issue_is_an_error() {
+        let base = start_recorded(Arc::new(Recorded::new())).await;
+        let query = TicketQuery { numbers: vec![404], label: None };
+        match github(base).fetch(&query).await {
+            Err(TrackerError::Status { status: 404, .. }) => {}
+            other => panic!("expected a 404, got {:?}", other.map(|t| t.len())),
+        }
+    }
+
+    #[tokio::test]
+    async fn estimate_replaces_previous_label() {
+        let server = Arc::new(
+            Recorded::new()
+                .with("GET", "/repos/acme/app/issues/12", 200, fixture("github/issue_12.json"))
+                .with("DELETE", "/repos/acme/app/issues/12/labels/estimate:%203", 200, "[]".to_string())
+                .with("POST", "/repos/acme/app/issues/12/labels", 200, fixture("github/labels_added.json")),
+        );
+        let base = start_recorded(server.clone()).await;
+
+        let ticket = Ticket { key: Some("acme/app#12".into()), source: Some(TrackerKind::Github), ..Ticket::new("Login fails") };
+        github(base).write_estimate(&ticket, 5).await.unwrap();
+
+        let requests = server.requests.lock().unwrap();
+        let calls: Vec<_> = requests.iter().map(|r| format!("{} {}", r.method, r.uri)).collect();
+        assert_eq!(
+            calls,
+            vec![
+                "GET /repos/acme/app/issues/12",
+                "DELETE /repos/acme/app/issues/12/labels/estimate:%203",
+                "POST /repos/acme/app/issues/12/labels",
+            ]
+        );
+        let body: serde_json::Value = serde_json::from_slice(&requests[2].body).unwrap();
+        assert_eq!(body, serde_json::json!({ "labels": ["estimate: 5"] }));
+    }
+
+    #[tokio::test]
+    async fn unlinked_ticket_is_not_written() {
+        let base = start_recorded(Arc::new(Recorded::new())).await;
+        let result = github(base).write_estimate(&Ticket::new("Typed by hand"), 3).await;
+        assert!(matches!(result, Err(TrackerError::NotLinked(_))));
+    }
+
+    #[test]
+    fn api_url_must_be_http() {
+        let settings = |api_url: &str| GithubSettings { api_url: api_url.to_string(), repo: "acme/app".to_string(), token: None };
+        for bad in ["", "api.github.com", "ftp://example.com", "mailto:ops@example.com"] {
+            assert!(matches!(GithubIssues::new(&settings(bad)), Err(TrackerError::InvalidUrl(_))), "{bad:?}");
+        }
+
+        // Enterprise servers live under a path prefix, with or without the trailing slash
+        for base in ["https://ghe.example.com/api/v3", "https://ghe.example.com/api/v3/"] {
+            let github = GithubIssues::new(&settings(base)).unwrap();
+            assert_eq!(github.url("issues/12").unwrap().as_str(), "https://ghe.example.com/api/v3/repos/acme/app/issues/12");
+        }
+    }
+}
@@ -0,0 +1,187 @@
+use async_trait::async_trait;
+use common::{Ticket, TrackerKind};
+use serde::Deserialize;
+
+use super::{check, http_client, imported, issue_number, merge, TicketQuery, TicketSource, TrackerError, PAGE_SIZE};
+use crate::settings::{GitlabEstimate, GitlabSettings};
+
+#[derive(Debug, Deserialize)]
+struct Issue {
+    // Per-project number, the one shown as #12 in the UI
+    iid: u64,
+    title: String,
+    description: Option<String>,
+    web_url: String,
+    #[serde(default)]
+    labels: Vec<String>,
+}
+
+// GitLab issues through the v4 REST API.
+pub struct GitlabIssues {
+    client: reqwest::Client,
+    // `.../api/v4/projects/<id or url-encoded path>`
+    project_url: String,
+    project: String,
+    token: Option<String>,
+    estimate_as: GitlabEstimate,
+}
+
+impl GitlabIssues {
+    pub fn new(settings: &GitlabSettings) -> Self {
+        Self {
+            client: http_client(),
+            project_url: format!(
+                "{}/api/v4/projects/{}",
+                settings.url.trim_end_matches('/'),
+                settings.project.replace('/', "%2F")
+            ),
+            project: settings.project.clone(),
+            token: settings.token.clone(),
+            estimate_as: settings.estimate_as,
+        }
+    }
+
+    fn request(&self, method: reqwest::Method, path: &str) -> reqwest::RequestBuilder {
+        let mut request = self.client.request(method, format!("{}/{}", self.project_url, path));
+        if let Some(token) = &self.token {
+            request = request.header("PRIVATE-TOKEN", token);
+        }
+        request
+    }
+
+    fn ticket(&self, issue: Issue) -> Ticket {
+        imported(
+            TrackerKind::Gitlab,
+            format!("{}#{}", self.project, issue.iid),
+            issue.title,
+            issue.description,
+            issue.web_url,
+            issue.labels,
+        )
+    }
+}
+
+#[async_trait]
+impl TicketSource for GitlabIssues {
+    fn kind(&self) -> TrackerKind {
+        TrackerKind::Gitlab
+    }
+
+    async fn fetch(&self, query: &TicketQuery) -> Result<Vec<Ticket>, TrackerError> {
+        let mut by_number = Vec::new();
+        for iid in &query.numbers {
+            let response = self.request(reqwest::Method::GET, &format!("issues/{}", iid)).send().await?;
+            let issue: Issue = check(response).await?.json().await?;
+            by_number.push(self.ticket(issue));
+        }
+
+        let mut by_label = Vec::new();
+        if let Some(label) = &query.label {
+            let response = self
+                .request(reqwest::Method::GET, "issues")
+                .query(&[("state", "opened"), ("labels", label.as_str()), ("per_page", PAGE_SIZE)])
+                .send()
+                .await?;
+            let issues: Vec<Issue> = check(response).await?.json().await?;
+            by_label = issues.into_iter().map(|issue| self.ticket(issue)).collect();
+        }
+
+        Ok(merge(by_number, by_label))
+    }
+
+    async fn write_estimate(&self, ticket: &Ticket, estimate: u32) -> Result<(), TrackerError> {
+        let iid = issue_number(ticket)?;
+        let body = match self.estimate_as {
+            GitlabEstimate::Weight => serde_json::json!({ "weight": estimate }),
+            // Scoped labels are exclusive, so this replaces any earlier estimate::<n>
+            GitlabEstimate::Label => serde_json::json!({ "add_labels": format!("estimate::{}", estimate) }),
+        };
+        let response = self
+            .request(reqwest::Method::PUT, &format!("issues/{}", iid))
+            .json(&body)
+            .send()
+            .await?;
+        check(response).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{fixture, start_recorded, Recorded};
+    use std::sync::Arc;
+
+    fn gitlab(base: String, estimate_as: GitlabEstimate) -> GitlabIssues {
+        GitlabIssues::new(&GitlabSettings {
+            url: base,
+            project: "acme/platform".to_string(),
+            token: Some("glpat-test".to_string()),
+            estimate_as,
+        })
+    }
+
+    #[tokio::test]
+    async fn fetches_by_number_and_label() {
+        let server = Arc::new(
+            Recorded::new()
+                .with("GET", "/api/v4/projects/acme%2Fplatform/issues/7", 200, fixture("gitlab/issue_7.json"))
+                .with("GET", "/api/v4/projects/acme%2Fplatform/issues?state=opened&labels=backlog&per_page=100", 200, fixture("gitlab/issues_labelled.json")),
+        );
+        let base = start_recorded(server.clone()).await;
+
+        let query = TicketQuery { numbers: vec![7], label: Some("backlog".to_string()) };
+        let tickets = gitlab(base, GitlabEstimate::Weight).fetch(&query).await.unwrap();
+
+        let keys: Vec<_> = tickets.iter().map(|t| t.key.clone().unwrap()).collect();
+        assert_eq!(keys, vec!["acme/platform#7", "acme/platform#9"]);
+        assert_eq!(tickets[0].title, "Rate limit the export endpoint");
+        assert_eq!(tickets[0].description.as_deref(), Some("Large exports can saturate the API workers."));
+        assert_eq!(tickets[0].url.as_deref(), Some("https://gitlab.example.com/acme/platform/-/issues/7"));
+        assert_eq!(tickets[0].labels, vec!["backend", "backlog"]);
+        assert!(tickets.iter().all(|t| t.source == Some(TrackerKind::Gitlab)));
+        assert_eq!(server.requests.lock().unwrap()[0].headers["private-token"], "glpat-test");
+    }
+
+    #[tokio::test]
+    async fn estimate_as_weight() {
+        let server = Arc::new(
+            Recorded::new().with("PUT", "/api/v4/projects/acme%2Fplatform/issues/7", 200, fixture("gitlab/issue_7.json")),
+        );
+        let base = start_recorded(server.clone()).await;
+
+        let ticket = Ticket { key: Some("acme/platform#7".into()), source: Some(TrackerKind::Gitlab), ..Ticket::new("Rate limit") };
+        gitlab(base, GitlabEstimate::Weight).write_estimate(&ticket, 8).await.unwrap();
+
+        let requests = server.requests.lock().unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+        assert_eq!(body, serde_json::json!({ "weight": 8 }));
+    }
+
+    #[tokio::test]
+    async fn estimate_as_scoped_label() {
+        let server = Arc::new(
+            Recorded::new().with("PUT", "/api/v4/projects/acme%2Fplatform/issues/7", 200, fixture("gitlab/issue_7.json")),
+        );
+        let base = start_recorded(server.clone()).await;
+
+        let ticket = Ticket { key: Some("acme/platform#7".into()), source: Some(TrackerKind::Gitlab), ..Ticket::new("Rate limit") };
+        gitlab(base, GitlabEstimate::Label).write_estimate(&ticket, 3).await.unwrap();
+
+        let requests = server.requests.lock().unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+        assert_eq!(body, serde_json::json!({ "add_labels": "estimate::3" }));
+    }
+
+    #[tokio::test]
+    async fn rejected_write_is_an_error() {
+        let server = Arc::new(
+            Recorded::new().with("PUT", "/api/v4/projects/acme%2Fplatform/issues/7", 403, r#"{"message":"403 Forbidden"}"#.to_string()),
+        );
+        let base = start_recorded(server).await;
+
+        let ticket = Ticket { key: Some("acme/platform#7".into()), source: Some(TrackerKind::Gitlab), ..Ticket::new("Rate limit") };
+        let result = gitlab(base, GitlabEstimate::Weight).write_estimate(&ticket, 3).await;
+        assert!(matches!(result, Err(TrackerError::Status { status: 403, .. })));
+    }
+}
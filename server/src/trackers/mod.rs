@@ -0,0 +1,285 @@
+use async_trait::async_trait;
+use common::{
+    Ticket, TrackerKind, MAX_TICKET_DESCRIPTION_LEN, MAX_TICKET_LABELS, MAX_TICKET_LABEL_LEN, MAX_TICKET_TITLE_LEN,
+};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast;
+
+use crate::events::{RoundEvent, RoundEventKind};
+use crate::settings::Settings;
+use crate::webhooks::forward_events;
+
+mod github;
+mod gitlab;
+
+pub use github::GithubIssues;
+pub use gitlab::GitlabIssues;
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+// Trackers cap a page at 100; more than that is more than a session can estimate anyway
+const PAGE_SIZE: &str = "100";
+
+// Which issues to import: explicit numbers, every open issue with a label, or both.
+#[derive(Debug, Clone, Default, Deserialize, JsonSchema)]
+pub struct TicketQuery {
+    #[serde(default)]
+    pub numbers: Vec<u64>,
+    pub label: Option<String>,
+}
+
+#[derive(Debug)]
+pub enum TrackerError {
+    Request(reqwest::Error),
+    // The tracker answered with a non-success status
+    Status { status: u16, body: String },
+    // The ticket has no issue number this tracker can use
+    NotLinked(String),
+    // The configured API base isn't an http(s) URL
+    InvalidUrl(String),
+}
+
+impl std::fmt::Display for TrackerError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Request(e) => write!(f, "request failed: {}", e),
+            Self::Status { status, body } => write!(f, "tracker answered {}: {}", status, body),
+            Self::NotLinked(title) => write!(f, "ticket '{}' is not linked to an issue", title),
+            Self::InvalidUrl(url) => write!(f, "'{}' is not a usable API URL", url),
+        }
+    }
+}
+
+impl std::error::Error for TrackerError {}
+
+impl From<reqwest::Error> for TrackerError {
+    fn from(e: reqwest::Error) -> Self {
+        Self::Request(e)
+    }
+}
+
+// An issue tracker tickets can be imported from and estimates written back to.
+#[async_trait]
+pub trait TicketSource: Send + Sync {
+    fn kind(&self) -> TrackerKind;
+
+    async fn fetch(&self, query: &TicketQuery) -> Result<Vec<Ticket>, TrackerError>;
+
+    // Only called with tickets this source produced.
+    async fn write_estimate(&self, ticket: &Ticket, estimate: u32) -> Result<(), TrackerError>;
+}
+
+// The configured trackers, one per kind.
+#[derive(Clone, Default)]
+pub struct Trackers {
+    sources: HashMap<TrackerKind, Arc<dyn TicketSource>>,
+}
+
+impl Trackers {
+    pub fn from_settings(settings: &Settings) -> Self {
+        let mut trackers = Self::default();
+        if let Some(github) = &settings.github {
+            match GithubIssues::new(github) {
+                Ok(source) => trackers.add(Arc::new(source)),
+                Err(e) => tracing::error!(error = %e, "GitHub tracker disabled"),
+            }
+        }
+        if let Some(gitlab) = &settings.gitlab {
+            trackers.add(Arc::new(GitlabIssues::new(gitlab)));
+        }
+        trackers
+    }
+
+    pub fn add(&mut self, source: Arc<dyn TicketSource>) {
+        self.sources.insert(source.kind(), source);
+    }
+
+    pub fn get(&self, kind: TrackerKind) -> Option<Arc<dyn TicketSource>> {
+        self.sources.get(&kind).cloned()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sources.is_empty()
+    }
+
+    // Write every final estimate back to the tracker its ticket came from.
+    pub fn spawn_write_back(self, events: broadcast::Receiver<RoundEvent>) -> tokio::task::JoinHandle<()> {
+        forward_events(events, move |event| {
+            if event.event != RoundEventKind::EstimateSet {
+                return;
+            }
+            let (Some(ticket), Some(estimate)) = (event.ticket, event.final_estimate) else {
+                return;
+            };
+            let Some(source) = ticket.source.and_then(|kind| self.get(kind)) else {
+                return;
+            };
+            tokio::spawn(async move {
+                match source.write_estimate(&ticket, estimate).await {
+                    Ok(()) => tracing::info!(ticket = ticket.key.as_deref().unwrap_or(""), estimate, "estimate written to tracker"),
+                    Err(e) => tracing::warn!(ticket = ticket.key.as_deref().unwrap_or(""), error = %e, "failed to write estimate to tracker"),
+                }
+            });
+        })
+    }
+}
+
+pub(crate) fn http_client() -> reqwest::Client {
+    reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .user_agent(concat!("poker-planning-server/", env!("CARGO_PKG_VERSION")))
+        .build()
+        .expect("default HTTP client")
+}
+
+// Turn a non-success response into an error, keeping the body for the logs.
+pub(crate) async fn check(response: reqwest::Response) -> Result<reqwest::Response, TrackerError> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+    let body = response.text().await.unwrap_or_default();
+    Err(TrackerError::Status { status: status.as_u16(), body })
+}
+
+// Trailing issue number of a key like "owner/repo#12".
+pub(crate) fn issue_number(ticket: &Ticket) -> Result<u64, TrackerError> {
+    ticket
+        .key
+        .as_deref()
+        .and_then(|key| key.rsplit_once('#'))
+        .and_then(|(_, number)| number.parse().ok())
+        .ok_or_else(|| TrackerError::NotLinked(ticket.title.clone()))
+}
+
+// Build a ticket from tracker fields, cut down to what the room accepts so one
+// long issue doesn't fail the whole import.
+pub(crate) fn imported(
+    source: TrackerKind,
+    key: String,
+    title: String,
+    description: Option<String>,
+    url: String,
+    labels: Vec<String>,
+) -> Ticket {
+    Ticket {
+        key: Some(key),
+        description: description
+            .map(|d| clip(d.trim(), MAX_TICKET_DESCRIPTION_LEN))
+            .filter(|d| !d.is_empty()),
+        url: Some(url),
+        labels: labels.iter().take(MAX_TICKET_LABELS).map(|l| clip(l, MAX_TICKET_LABEL_LEN)).collect(),
+        source: Some(source),
+        ..Ticket::new(clip(&title, MAX_TICKET_TITLE_LEN))
+    }
+}
+
+fn clip(text: &str, max_chars: usize) -> String {
+    match text.char_indices().nth(max_chars.saturating_sub(1)) {
+        Some((end, _)) if text.chars().count() > max_chars => format!("{}…", &text[..end]),
+        _ => text.to_string(),
+    }
+}
+
+// Numbers first, in the order given, then label matches; each issue once.
+pub(crate) fn merge(by_number: Vec<Ticket>, by_label: Vec<Ticket>) -> Vec<Ticket> {
+    let mut tickets = by_number;
+    for ticket in by_label {
+        if !tickets.iter().any(|t| t.key == ticket.key) {
+            tickets.push(ticket);
+        }
+    }
+    tickets
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metrics::Metrics;
+    use crate::room::RoomHandle;
+    use crate::state::ServerState;
+    use crate::test_support::{temp_settings, wait_for};
+    use common::AdminCommand;
+    use std::sync::Mutex;
+
+    // Records write-backs instead of calling a tracker.
+    #[derive(Default)]
+    struct Stub {
+        written: Mutex<Vec<(Option<String>, u32)>>,
+    }
+
+    #[async_trait]
+    impl TicketSource for Stub {
+        fn kind(&self) -> TrackerKind {
+            TrackerKind::Github
+        }
+
+        async fn fetch(&self, _query: &TicketQuery) -> Result<Vec<Ticket>, TrackerError> {
+            Ok(Vec::new())
+        }
+
+        async fn write_estimate(&self, ticket: &Ticket, estimate: u32) -> Result<(), TrackerError> {
+            self.written.lock().unwrap().push((ticket.key.clone(), estimate));
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn estimates_for_imported_tickets_are_written_back() {
+        let stub = Arc::new(Stub::default());
+        let mut trackers = Trackers::default();
+        trackers.add(stub.clone());
+
+        let (status_tx, _) = broadcast::channel(16);
+        let room = RoomHandle::spawn(ServerState::new(), temp_settings(), Arc::new(Metrics::new()), status_tx);
+        trackers.spawn_write_back(room.subscribe());
+
+        let imported = Ticket {
+            key: Some("acme/app#12".to_string()),
+            source: Some(TrackerKind::Github),
+            ..Ticket::new("Login fails with SSO accounts")
+        };
+        room.admin("test".into(), AdminCommand::SetBacklog { tickets: vec![imported] }).await.unwrap().unwrap();
+
+        // A hand-typed ticket has nowhere to go
+        room.start_voting("test".into(), Some(Ticket::new("PROJ-1"))).await.unwrap();
+        room.reveal("test".into()).await.unwrap().unwrap();
+        room.admin("test".into(), AdminCommand::SetEstimate { value: Some(2) }).await.unwrap().unwrap();
+
+        // Typing the imported key links the round to the issue
+        room.start_voting("test".into(), Some(Ticket::new("acme/app#12"))).await.unwrap();
+        room.reveal("test".into()).await.unwrap().unwrap();
+        room.admin("test".into(), AdminCommand::SetEstimate { value: Some(5) }).await.unwrap().unwrap();
+
+        wait_for(|| !stub.written.lock().unwrap().is_empty()).await;
+        assert_eq!(*stub.written.lock().unwrap(), vec![(Some("acme/app#12".to_string()), 5)]);
+    }
+
+    #[test]
+    fn issue_number_from_key() {
+        let ticket = Ticket { key: Some("group/sub/project#42".into()), source: Some(TrackerKind::Gitlab), ..Ticket::new("x") };
+        assert_eq!(issue_number(&ticket).unwrap(), 42);
+        assert!(issue_number(&Ticket::new("PROJ-1")).is_err());
+    }
+
+    #[test]
+    fn long_fields_are_clipped() {
+        assert_eq!(clip("abcdef", 4), "abc…");
+        assert_eq!(clip("abcd", 4), "abcd");
+        assert_eq!(clip("äöüß", 3), "äö…");
+
+        let mut labels: Vec<String> = (0..30).map(|i| format!("l{}", i)).collect();
+        labels[0] = "x".repeat(MAX_TICKET_LABEL_LEN + 10);
+        let long = "x".repeat(MAX_TICKET_DESCRIPTION_LEN + 10);
+        let ticket = imported(TrackerKind::Github, "a/b#1".into(), "Title".into(), Some(long), "https://x".into(), labels);
+        assert_eq!(ticket.description.unwrap().chars().count(), MAX_TICKET_DESCRIPTION_LEN);
+        assert_eq!(ticket.labels.len(), MAX_TICKET_LABELS);
+        assert_eq!(ticket.labels[0].chars().count(), MAX_TICKET_LABEL_LEN);
+
+        let blank = imported(TrackerKind::Github, "a/b#1".into(), "Title".into(), Some("  \n".into()), "https://x".into(), vec![]);
+        assert_eq!(blank.description, None);
+    }
+}
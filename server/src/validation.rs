@@ -0,0 +1,233 @@
+use common::{
+    ErrorCode, GameState, MapLayout, ServerPayload, Ticket, VotingConfig, MAX_BACKLOG, MAX_CARDS, MAX_NAME_LEN,
+    MAX_TICKET_DESCRIPTION_LEN, MAX_TICKET_KEY_LEN, MAX_TICKET_LABELS, MAX_TICKET_LABEL_LEN, MAX_TICKET_TITLE_LEN,
+    MAX_TICKET_URL_LEN,
+};
+use std::collections::HashSet;
+
+// A message the server refused to act on, reported back as `ServerPayload::Error`.
+#[derive(Debug, Clone)]
+pub struct Rejection {
+    pub code: ErrorCode,
+    pub message: String,
+}
+
+impl Rejection {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self { code, message: message.into() }
+    }
+
+    pub fn into_payload(self) -> ServerPayload {
+        ServerPayload::Error { code: self.code, message: self.message }
+    }
+}
+
+// Trims the name and checks length and case-insensitive uniqueness.
+pub fn validate_name(name: &str, state: &GameState) -> Result<String, Rejection> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(Rejection::new(ErrorCode::InvalidName, "Name cannot be empty"));
+    }
+    if name.chars().count() > MAX_NAME_LEN {
+        return Err(Rejection::new(
+            ErrorCode::InvalidName,
+            format!("Name must be at most {} characters", MAX_NAME_LEN),
+        ));
+    }
+    if name.chars().any(char::is_control) {
+        return Err(Rejection::new(ErrorCode::InvalidName, "Name contains control characters"));
+    }
+    let lower = name.to_lowercase();
+    if state.players.values().any(|p| p.name.to_lowercase() == lower) {
+        return Err(Rejection::new(ErrorCode::NameTaken, format!("Name '{}' is already taken", name)));
+    }
+    Ok(name.to_string())
+}
+
+pub fn validate_position(x: u16, y: u16, map: &MapLayout) -> Result<(), Rejection> {
+    if !map.contains(x, y) {
+        return Err(Rejection::new(
+            ErrorCode::OutOfBounds,
+            format!("({}, {}) is outside the {}x{} map", x, y, map.width, map.height),
+        ));
+    }
+    Ok(())
+}
+
+// `None` (withdrawing a vote) is always allowed.
+pub fn validate_vote(value: Option<u32>, config: &VotingConfig) -> Result<(), Rejection> {
+    match value {
+        Some(card) if !config.cards.contains(&card) => Err(Rejection::new(
+            ErrorCode::InvalidCard,
+            format!("{} is not a card in this deck", card),
+        )),
+        _ => Ok(()),
+    }
+}
+
+pub fn validate_config(config: &VotingConfig) -> Result<(), Rejection> {
+    if config.cards.is_empty() {
+        return Err(Rejection::new(ErrorCode::InvalidConfig, "Deck must contain at least one card"));
+    }
+    if config.cards.len() > MAX_CARDS {
+        return Err(Rejection::new(
+            ErrorCode::InvalidConfig,
+            format!("Deck can contain at most {} cards", MAX_CARDS),
+        ));
+    }
+    let unique: HashSet<_> = config.cards.iter().collect();
+    if unique.len() != config.cards.len() {
+        return Err(Rejection::new(ErrorCode::InvalidConfig, "Deck contains duplicate cards"));
+    }
+    validate_timeout(config.default_timeout)
+}
+
+// `None` means the round has no timer.
+pub fn validate_timeout(timeout: Option<u64>) -> Result<(), Rejection> {
+    if timeout == Some(0) {
+        return Err(Rejection::new(ErrorCode::InvalidConfig, "Timeout must be positive"));
+    }
+    Ok(())
+}
+
+pub fn validate_backlog(tickets: &[Ticket]) -> Result<(), Rejection> {
+    if tickets.len() > MAX_BACKLOG {
+        return Err(Rejection::new(
+            ErrorCode::InvalidConfig,
+            format!("Backlog can hold at most {} tickets", MAX_BACKLOG),
+        ));
+    }
+    tickets.iter().try_for_each(validate_ticket)
+}
+
+pub fn validate_ticket(ticket: &Ticket) -> Result<(), Rejection> {
+    if ticket.title.trim().is_empty() {
+        return Err(Rejection::new(ErrorCode::InvalidTicket, "Tickets need a title"));
+    }
+    if ticket.title.chars().count() > MAX_TICKET_TITLE_LEN {
+        return Err(Rejection::new(
+            ErrorCode::InvalidTicket,
+            format!("Ticket titles can be at most {} characters", MAX_TICKET_TITLE_LEN),
+        ));
+    }
+    if ticket.description.as_ref().is_some_and(|d| d.chars().count() > MAX_TICKET_DESCRIPTION_LEN) {
+        return Err(Rejection::new(
+            ErrorCode::InvalidTicket,
+            format!("Ticket descriptions can be at most {} characters", MAX_TICKET_DESCRIPTION_LEN),
+        ));
+    }
+    if ticket.key.as_ref().is_some_and(|k| k.chars().count() > MAX_TICKET_KEY_LEN) {
+        return Err(Rejection::new(
+            ErrorCode::InvalidTicket,
+            format!("Ticket keys can be at most {} characters", MAX_TICKET_KEY_LEN),
+        ));
+    }
+    if ticket.url.as_ref().is_some_and(|u| u.chars().count() > MAX_TICKET_URL_LEN) {
+        return Err(Rejection::new(
+            ErrorCode::InvalidTicket,
+            format!("Ticket links can be at most {} characters", MAX_TICKET_URL_LEN),
+        ));
+    }
+    if ticket.labels.len() > MAX_TICKET_LABELS {
+        return Err(Rejection::new(
+            ErrorCode::InvalidTicket,
+            format!("Tickets can have at most {} labels", MAX_TICKET_LABELS),
+        ));
+    }
+    if ticket.labels.iter().any(|l| l.chars().count() > MAX_TICKET_LABEL_LEN) {
+        return Err(Rejection::new(
+            ErrorCode::InvalidTicket,
+            format!("Ticket labels can be at most {} characters", MAX_TICKET_LABEL_LEN),
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::ServerState;
+    use common::{AvatarColor, AvatarSymbol, Player, Presence, Role, StateEvent};
+    use uuid::Uuid;
+
+    fn room_with(name: &str) -> GameState {
+        let mut state = ServerState::new();
+        state.apply(StateEvent::PlayerJoined(Player {
+            id: Uuid::new_v4(),
+            name: name.to_string(),
+            role: Role::Participant,
+            position: (0, 0),
+            color: AvatarColor::Red,
+            symbol: AvatarSymbol::Human,
+            confirmed: false,
+            presence: Presence::Active,
+        }));
+        state.game_state
+    }
+
+    fn code<T: std::fmt::Debug>(result: Result<T, Rejection>) -> ErrorCode {
+        result.unwrap_err().code
+    }
+
+    #[test]
+    fn names_are_trimmed_and_checked() {
+        let room = room_with("Alice");
+        assert_eq!(validate_name("  Bob ", &room).unwrap(), "Bob");
+        assert_eq!(validate_name(&"x".repeat(MAX_NAME_LEN), &room).unwrap().len(), MAX_NAME_LEN);
+
+        assert_eq!(code(validate_name("   ", &room)), ErrorCode::InvalidName);
+        assert_eq!(code(validate_name(&"x".repeat(MAX_NAME_LEN + 1), &room)), ErrorCode::InvalidName);
+        assert_eq!(code(validate_name("Bo\u{7}b", &room)), ErrorCode::InvalidName);
+        assert_eq!(code(validate_name(" ALICE", &room)), ErrorCode::NameTaken);
+    }
+
+    #[test]
+    fn votes_must_be_in_the_deck() {
+        let config = VotingConfig { cards: vec![1, 2, 3], default_timeout: None };
+        assert!(validate_vote(Some(2), &config).is_ok());
+        assert!(validate_vote(None, &config).is_ok());
+        assert_eq!(code(validate_vote(Some(5), &config)), ErrorCode::InvalidCard);
+    }
+
+    #[test]
+    fn decks_need_distinct_cards_within_the_limit() {
+        let deck = |cards: Vec<u32>| VotingConfig { cards, default_timeout: None };
+        assert!(validate_config(&VotingConfig::default()).is_ok());
+        assert!(validate_config(&deck((1..=MAX_CARDS as u32).collect())).is_ok());
+
+        assert_eq!(code(validate_config(&deck(vec![]))), ErrorCode::InvalidConfig);
+        assert_eq!(code(validate_config(&deck((0..=MAX_CARDS as u32).collect()))), ErrorCode::InvalidConfig);
+        assert_eq!(code(validate_config(&deck(vec![1, 2, 2]))), ErrorCode::InvalidConfig);
+    }
+
+    #[test]
+    fn timeouts_must_be_positive() {
+        assert!(validate_timeout(None).is_ok());
+        assert!(validate_timeout(Some(30)).is_ok());
+        assert_eq!(code(validate_timeout(Some(0))), ErrorCode::InvalidConfig);
+        let config = VotingConfig { cards: vec![1], default_timeout: Some(0) };
+        assert_eq!(code(validate_config(&config)), ErrorCode::InvalidConfig);
+    }
+
+    #[test]
+    fn tickets_are_bounded_field_by_field() {
+        let ticket = Ticket { key: Some("PROJ-1".into()), url: Some("https://example.com/PROJ-1".into()), ..Ticket::new("Export") };
+        assert!(validate_ticket(&ticket).is_ok());
+
+        let too_long = |n: usize| Some("x".repeat(n + 1));
+        let bad = [
+            Ticket::new(" "),
+            Ticket::new("x".repeat(MAX_TICKET_TITLE_LEN + 1)),
+            Ticket { key: too_long(MAX_TICKET_KEY_LEN), ..ticket.clone() },
+            Ticket { url: too_long(MAX_TICKET_URL_LEN), ..ticket.clone() },
+            Ticket { description: too_long(MAX_TICKET_DESCRIPTION_LEN), ..ticket.clone() },
+            Ticket { labels: vec!["l".into(); MAX_TICKET_LABELS + 1], ..ticket.clone() },
+            Ticket { labels: vec!["x".repeat(MAX_TICKET_LABEL_LEN + 1)], ..ticket.clone() },
+        ];
+        for ticket in &bad {
+            assert_eq!(code(validate_ticket(ticket)), ErrorCode::InvalidTicket);
+        }
+        assert_eq!(code(validate_backlog(&bad[..1])), ErrorCode::InvalidTicket);
+        assert_eq!(code(validate_backlog(&vec![ticket; MAX_BACKLOG + 1])), ErrorCode::InvalidConfig);
+    }
+}
@@ -0,0 +1,333 @@
+use hmac::{Hmac, Mac};
+use schemars::JsonSchema;
+use serde::Serialize;
+use sha2::Sha256;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+use crate::events::{RoundEvent, RoundEventKind};
+use crate::settings::Settings;
+
+// `sha256=<hex HMAC-SHA256 of the raw body>`, keyed with POKER_WEBHOOK_SECRET.
+pub const SIGNATURE_HEADER: &str = "x-poker-signature";
+pub const EVENT_HEADER: &str = "x-poker-event";
+pub const DELIVERY_HEADER: &str = "x-poker-delivery";
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+const FIRST_BACKOFF: Duration = Duration::from_millis(500);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+const MAX_LOGGED: usize = 500;
+
+// Outcome of sending one event to one URL, after any retries.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct Delivery {
+    pub id: Uuid,
+    pub url: String,
+    pub event: RoundEventKind,
+    pub timestamp_unix: u64,
+    pub attempts: u32,
+    // HTTP status of the last attempt, if the receiver answered at all
+    pub status: Option<u16>,
+    pub ok: bool,
+    pub error: Option<String>,
+}
+
+// Recent deliveries, newest last, served at `GET /api/webhooks/deliveries`.
+#[derive(Default)]
+pub struct DeliveryLog {
+    entries: Mutex<VecDeque<Delivery>>,
+}
+
+impl DeliveryLog {
+    pub fn record(&self, delivery: Delivery) {
+        let mut entries = self.entries.lock().unwrap();
+        entries.push_back(delivery);
+        if entries.len() > MAX_LOGGED {
+            entries.pop_front();
+        }
+    }
+
+    pub fn recent(&self) -> Vec<Delivery> {
+        self.entries.lock().unwrap().iter().cloned().collect()
+    }
+}
+
+pub fn sign(secret: &str, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC takes keys of any length");
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+// POSTs a body to one URL, retrying network errors, 5xx and 429 with
+// exponential backoff, and records the outcome in the delivery log.
+#[derive(Clone)]
+pub struct Poster {
+    client: reqwest::Client,
+    max_attempts: u32,
+    first_backoff: Duration,
+    log: Arc<DeliveryLog>,
+}
+
+impl Poster {
+    pub fn new(max_attempts: u32, log: Arc<DeliveryLog>) -> Self {
+        Self {
+            client: reqwest::Client::builder()
+                .timeout(REQUEST_TIMEOUT)
+                .build()
+                .expect("default HTTP client"),
+            max_attempts: max_attempts.max(1),
+            first_backoff: FIRST_BACKOFF,
+            log,
+        }
+    }
+
+    pub async fn deliver(&self, url: &str, event: RoundEventKind, body: &[u8], signature: Option<String>) -> Delivery {
+        let id = Uuid::new_v4();
+        let mut backoff = self.first_backoff;
+        let mut attempts = 0;
+        let mut status = None;
+        let mut error = None;
+
+        while attempts < self.max_attempts {
+            if attempts > 0 {
+                tokio::time::sleep(backoff).await;
+                backoff = (backoff * 2).min(MAX_BACKOFF);
+            }
+            attempts += 1;
+
+            let mut request = self
+                .client
+                .post(url)
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .header(EVENT_HEADER, event.as_str())
+                .header(DELIVERY_HEADER, id.to_string())
+                .body(body.to_vec());
+            if let Some(signature) = &signature {
+                request = request.header(SIGNATURE_HEADER, signature);
+            }
+
+            match request.send().await {
+                Ok(response) => {
+                    let code = response.status();
+                    status = Some(code.as_u16());
+                    if code.is_success() {
+                        error = None;
+                        break;
+                    }
+                    error = Some(format!("receiver answered {}", code));
+                    // Other 4xx mean the receiver won't accept this payload, so don't retry
+                    if code.is_client_error() && code != reqwest::StatusCode::TOO_MANY_REQUESTS {
+                        break;
+                    }
+                }
+                Err(e) => {
+                    status = None;
+                    error = Some(e.to_string());
+                }
+            }
+            tracing::debug!(%url, attempt = attempts, error = error.as_deref().unwrap_or(""), "delivery attempt failed");
+        }
+
+        let ok = error.is_none();
+        if ok {
+            tracing::debug!(%url, event = event.as_str(), attempts, "delivered");
+        } else {
+            tracing::warn!(%url, event = event.as_str(), attempts, error = error.as_deref().unwrap_or(""), "delivery failed");
+        }
+        let delivery = Delivery {
+            id,
+            url: url.to_string(),
+            event,
+            timestamp_unix: common::current_time_unix(),
+            attempts,
+            status,
+            ok,
+            error,
+        };
+        self.log.record(delivery.clone());
+        delivery
+    }
+}
+
+// Forward round events from the room until it goes away. `handle` runs once
+// per event; anything slow should be spawned so later events aren't held up.
+pub fn forward_events(
+    mut events: broadcast::Receiver<RoundEvent>,
+    mut handle: impl FnMut(RoundEvent) + Send + 'static,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        loop {
+            match events.recv().await {
+                Ok(event) => handle(event),
+                Err(broadcast::error::RecvError::Lagged(missed)) => {
+                    tracing::warn!(missed, "integration fell behind, events dropped");
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    })
+}
+
+// Posts every round event, as JSON, to each configured URL. Each delivery
+// runs on its own task so a slow or dead receiver doesn't hold up the others.
+pub struct Webhooks {
+    poster: Poster,
+    urls: Vec<String>,
+    secret: Option<String>,
+}
+
+impl Webhooks {
+    pub fn new(settings: &Settings, log: Arc<DeliveryLog>) -> Self {
+        Self {
+            poster: Poster::new(settings.webhook_max_attempts, log),
+            urls: settings.webhook_urls.clone(),
+            secret: settings.webhook_secret.clone(),
+        }
+    }
+
+    pub fn spawn(self, events: broadcast::Receiver<RoundEvent>) -> tokio::task::JoinHandle<()> {
+        forward_events(events, move |event| {
+            let body = Arc::new(serde_json::to_vec(&event).unwrap());
+            let signature = self.secret.as_deref().map(|secret| sign(secret, &body));
+            for url in &self.urls {
+                let poster = self.poster.clone();
+                let url = url.clone();
+                let body = body.clone();
+                let signature = signature.clone();
+                tokio::spawn(async move {
+                    poster.deliver(&url, event.event, &body, signature).await;
+                });
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metrics::Metrics;
+    use crate::room::RoomHandle;
+    use crate::state::ServerState;
+    use crate::test_support::{start_receiver, temp_settings, wait_for, Receiver};
+    use axum::http::StatusCode;
+    use common::AdminCommand;
+    use std::sync::atomic::Ordering;
+
+    fn poster(max_attempts: u32) -> Poster {
+        let mut poster = Poster::new(max_attempts, Arc::new(DeliveryLog::default()));
+        poster.first_backoff = Duration::from_millis(10);
+        poster
+    }
+
+    #[tokio::test]
+    async fn delivers_signed_payload() {
+        let receiver = Arc::new(Receiver::default());
+        let url = start_receiver(receiver.clone()).await;
+        let poster = poster(3);
+
+        let event = RoundEvent::new(RoundEventKind::VotesRevealed, &ServerState::new());
+        let body = serde_json::to_vec(&event).unwrap();
+        let delivery = poster.deliver(&url, RoundEventKind::VotesRevealed, &body, Some(sign("s3cret", &body))).await;
+        assert!(delivery.ok);
+        assert_eq!(delivery.attempts, 1);
+        assert_eq!(delivery.status, Some(200));
+        assert_eq!(poster.log.recent().len(), 1);
+
+        let requests = receiver.requests.lock().unwrap();
+        let (headers, received) = &requests[0];
+        assert_eq!(received, &body);
+        assert_eq!(headers[EVENT_HEADER], "votes_revealed");
+        assert_eq!(headers[SIGNATURE_HEADER].to_str().unwrap(), sign("s3cret", &body));
+        assert_eq!(headers[DELIVERY_HEADER].to_str().unwrap(), delivery.id.to_string());
+    }
+
+    #[test]
+    fn signature_is_hmac_sha256() {
+        // echo -n 'hello' | openssl dgst -sha256 -hmac key
+        assert_eq!(
+            sign("key", b"hello"),
+            "sha256=9307b3b915efb5171ff14d8cb55fbcc798c6c0ef1456d66ded1a6aa723a58b7b"
+        );
+    }
+
+    #[tokio::test]
+    async fn retries_server_errors_with_backoff() {
+        let receiver = Arc::new(Receiver::default());
+        receiver.fail_first.store(2, Ordering::SeqCst);
+        let url = start_receiver(receiver.clone()).await;
+
+        let delivery = poster(5).deliver(&url, RoundEventKind::RoundStarted, b"{}", None).await;
+        assert!(delivery.ok);
+        assert_eq!(delivery.attempts, 3);
+        // Every attempt of one delivery carries the same id
+        let requests = receiver.requests.lock().unwrap();
+        assert!(requests.iter().all(|(h, _)| h[DELIVERY_HEADER] == delivery.id.to_string()));
+    }
+
+    #[tokio::test]
+    async fn gives_up_after_max_attempts() {
+        let receiver = Arc::new(Receiver::default());
+        receiver.fail_first.store(10, Ordering::SeqCst);
+        let url = start_receiver(receiver.clone()).await;
+
+        let delivery = poster(3).deliver(&url, RoundEventKind::RoundStarted, b"{}", None).await;
+        assert!(!delivery.ok);
+        assert_eq!(delivery.attempts, 3);
+        assert_eq!(delivery.status, Some(500));
+        assert_eq!(receiver.requests.lock().unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn does_not_retry_client_errors() {
+        let receiver = Arc::new(Receiver::default());
+        receiver.fail_first.store(10, Ordering::SeqCst);
+        *receiver.fail_with.lock().unwrap() = Some(StatusCode::GONE);
+        let url = start_receiver(receiver.clone()).await;
+
+        let delivery = poster(5).deliver(&url, RoundEventKind::RoundStarted, b"{}", None).await;
+        assert!(!delivery.ok);
+        assert_eq!(delivery.attempts, 1);
+        assert_eq!(delivery.status, Some(410));
+    }
+
+    #[tokio::test]
+    async fn room_events_reach_the_receiver() {
+        let receiver = Arc::new(Receiver::default());
+        let url = start_receiver(receiver.clone()).await;
+        let log = Arc::new(DeliveryLog::default());
+
+        let settings = Settings {
+            webhook_urls: vec![url],
+            webhook_secret: Some("s3cret".to_string()),
+            ..temp_settings()
+        };
+        let (status_tx, _) = broadcast::channel(16);
+        let room = RoomHandle::spawn(ServerState::new(), settings.clone(), Arc::new(Metrics::new()), status_tx);
+        Webhooks::new(&settings, log.clone()).spawn(room.subscribe());
+
+        room.start_voting("test".into(), None).await.unwrap();
+        room.reveal("test".into()).await.unwrap().unwrap();
+        room.admin("test".into(), AdminCommand::SetEstimate { value: Some(5) }).await.unwrap().unwrap();
+
+        wait_for(|| log.recent().len() == 3).await;
+        let mut events: Vec<_> = log.recent().into_iter().map(|d| (d.event, d.ok)).collect();
+        events.sort_by_key(|(e, _)| e.as_str());
+        assert_eq!(
+            events,
+            vec![
+                (RoundEventKind::EstimateSet, true),
+                (RoundEventKind::RoundStarted, true),
+                (RoundEventKind::VotesRevealed, true),
+            ]
+        );
+
+        let estimate = receiver.bodies().into_iter().find(|v| v["event"] == "estimate_set").unwrap();
+        assert_eq!(estimate["final_estimate"], 5);
+        for (headers, body) in receiver.requests.lock().unwrap().iter() {
+            assert_eq!(headers[SIGNATURE_HEADER].to_str().unwrap(), sign("s3cret", body));
+        }
+    }
+}
@@ -0,0 +1,329 @@
+// End-to-end tests: a real server on ephemeral ports, driven over TCP and HTTP.
+
+mod support;
+
+use std::time::Duration;
+
+use common::{AdminCommand, ErrorCode, Phase, Presence, Role, ServerPayload, StateEvent};
+use serde_json::json;
+use server::HIDDEN_POSITION;
+use support::{TestClient, TestServer};
+
+fn moved_or_voted(payloads: &[ServerPayload], id: uuid::Uuid) -> bool {
+    payloads.iter().any(|payload| match payload {
+        ServerPayload::Events(events) => events.iter().any(|event| {
+            matches!(event, StateEvent::PlayerMoved { player_id, .. } | StateEvent::VoteCast { player_id, .. } if *player_id == id)
+        }),
+        _ => false,
+    })
+}
+
+#[tokio::test]
+async fn votes_and_positions_stay_hidden_until_the_reveal() {
+    let server = TestServer::start().await;
+    let mut sam = server.join("Sam", Role::ScrumMaster).await;
+    let mut alice = server.join("Alice", Role::Participant).await;
+    let mut bob = server.join("Bob", Role::Participant).await;
+
+    sam.admin(AdminCommand::StartVote { ticket: None, timeout: None });
+    sam.sync().await;
+    bob.sync().await;
+    assert!(matches!(bob.state().phase, Phase::Voting { .. }));
+    assert_eq!(bob.state().players[&alice.id].position, HIDDEN_POSITION);
+
+    alice.vote_for(5);
+    alice.confirm();
+    alice.sync().await;
+    assert_eq!(alice.state().votes.get(&alice.id), Some(&Some(5)));
+
+    // Bob learns that Alice confirmed, but not where Alice went or which card
+    let seen = bob.sync().await;
+    assert!(!moved_or_voted(&seen, alice.id));
+    assert!(bob.state().players[&alice.id].confirmed);
+    assert_eq!(bob.state().players[&alice.id].position, HIDDEN_POSITION);
+    assert!(!bob.state().votes.contains_key(&alice.id));
+
+    // The reveal sends everyone the full picture
+    let zone = alice.state().map.zone_for_card(5).unwrap().center();
+    sam.admin(AdminCommand::Reveal);
+    sam.sync().await;
+    bob.expect("snapshot after the reveal", |p| matches!(p, ServerPayload::StateUpdate(_))).await;
+    assert_eq!(bob.state().phase, Phase::Revealed);
+    assert_eq!(bob.state().votes.get(&alice.id), Some(&Some(5)));
+    assert_eq!(bob.state().players[&alice.id].position, zone);
+}
+
+#[tokio::test]
+async fn late_joiners_and_resyncs_get_the_masked_view() {
+    let server = TestServer::start().await;
+    let mut sam = server.join("Sam", Role::ScrumMaster).await;
+    let mut alice = server.join("Alice", Role::Participant).await;
+    sam.admin(AdminCommand::StartVote { ticket: None, timeout: None });
+    sam.sync().await;
+    alice.vote_for(8);
+    alice.sync().await;
+
+    let mut carol = server.join("Carol", Role::Observer).await;
+    assert!(!carol.state().votes.contains_key(&alice.id));
+    assert_eq!(carol.state().players[&alice.id].position, HIDDEN_POSITION);
+
+    carol.send(common::ClientPayload::RequestSnapshot);
+    carol.expect("snapshot", |p| matches!(p, ServerPayload::StateUpdate(_))).await;
+    assert!(!carol.state().votes.contains_key(&alice.id));
+}
+
+#[tokio::test]
+async fn only_the_scrum_master_can_kick_and_kicked_players_are_told_and_hung_up_on() {
+    let server = TestServer::start().await;
+    let mut sam = server.join("Sam", Role::ScrumMaster).await;
+    let mut alice = server.join("Alice", Role::Participant).await;
+
+    alice.admin(AdminCommand::Kick { player_id: sam.id });
+    alice.expect_error(ErrorCode::NotAuthorized).await;
+
+    sam.admin(AdminCommand::Kick { player_id: alice.id });
+    let seen = sam.sync().await;
+    assert!(seen.iter().any(|p| matches!(p, ServerPayload::Events(events)
+        if events.iter().any(|e| matches!(e, StateEvent::PlayerLeft { player_id } if *player_id == alice.id)))));
+    assert!(!sam.state().players.contains_key(&alice.id));
+
+    alice.expect_error(ErrorCode::Kicked).await;
+    alice.expect_closed().await;
+}
+
+#[tokio::test]
+async fn dropped_connections_can_resume_their_session() {
+    let server = TestServer::start().await;
+    let mut sam = server.join("Sam", Role::ScrumMaster).await;
+    let alice = server.join("Alice", Role::Participant).await;
+    let (id, token) = (alice.id, alice.session_token);
+
+    drop(alice);
+    sam.expect_event("Alice disconnecting", |e| {
+        matches!(e, StateEvent::PresenceChanged { player_id, presence: Presence::Disconnected } if *player_id == id)
+    })
+    .await;
+
+    let alice = TestClient::login(server.tcp_addr, "Alice", Role::Participant, Some(token)).await;
+    assert_eq!(alice.id, id);
+    assert_eq!(alice.session_token, token);
+    sam.expect_event("Alice coming back", |e| {
+        matches!(e, StateEvent::PresenceChanged { player_id, presence: Presence::Active } if *player_id == id)
+    })
+    .await;
+    assert_eq!(sam.state().players.len(), 2);
+}
+
+#[tokio::test]
+async fn a_logged_in_connection_cannot_take_over_another_session() {
+    let server = TestServer::start().await;
+    let mut sam = server.join("Sam", Role::ScrumMaster).await;
+    let alice = server.join("Alice", Role::Participant).await;
+
+    sam.send(common::ClientPayload::Login {
+        name: "Sam".into(),
+        role: Role::ScrumMaster,
+        color: common::AvatarColor::Red,
+        symbol: common::AvatarSymbol::Human,
+        resume_token: Some(alice.session_token),
+    });
+    sam.expect_error(ErrorCode::AlreadyLoggedIn).await;
+
+    // The connection still speaks for Sam
+    let id = sam.id;
+    sam.move_to((2, 2));
+    sam.expect_event("Sam moving", |e| matches!(e, StateEvent::PlayerMoved { player_id, .. } if *player_id == id)).await;
+    assert_eq!(sam.state().players.len(), 2);
+}
+
+#[tokio::test]
+async fn a_restart_keeps_the_round_and_its_sessions() {
+    let server = TestServer::start().await;
+    let sam = server.join("Sam", Role::ScrumMaster).await;
+    let mut alice = server.join("Alice", Role::Participant).await;
+
+    let (status, _) = server.post("/api/start-voting", json!({ "issue_number": "PROJ-7", "title": "Export CSV" })).await;
+    assert_eq!(status, 200);
+    alice.expect("the round starting", |p| matches!(p, ServerPayload::StateUpdate(s) if matches!(s.phase, Phase::Voting { .. }))).await;
+    alice.vote_for(5);
+    let id = alice.id;
+    alice.expect_event("Alice's vote", |e| matches!(e, StateEvent::VoteCast { player_id, value: Some(5) } if *player_id == id)).await;
+
+    let (sam_token, alice_token) = (sam.session_token, alice.session_token);
+    let server = server.restart().await;
+
+    let alice = TestClient::login(server.tcp_addr, "Alice", Role::Participant, Some(alice_token)).await;
+    assert_eq!(alice.id, id);
+    let state = alice.state();
+    assert!(matches!(state.phase, Phase::Voting { .. }));
+    assert_eq!(state.current_ticket.as_ref().map(|t| t.title.as_str()), Some("Export CSV"));
+    assert_eq!(state.votes.get(&id), Some(&Some(5)));
+
+    let sam_id = sam.id;
+    let sam = TestClient::login(server.tcp_addr, "Sam", Role::ScrumMaster, Some(sam_token)).await;
+    assert_eq!(sam.id, sam_id);
+    assert_eq!(sam.state().players.len(), 2);
+}
+
+#[tokio::test]
+async fn players_who_stay_away_past_the_grace_period_leave_the_room() {
+    let server = TestServer::with_settings(|s| s.disconnect_grace = Duration::from_millis(100)).await;
+    let mut sam = server.join("Sam", Role::ScrumMaster).await;
+    let alice = server.join("Alice", Role::Participant).await;
+    let (id, token) = (alice.id, alice.session_token);
+
+    drop(alice);
+    sam.expect_event("Alice leaving", |e| matches!(e, StateEvent::PlayerLeft { player_id } if *player_id == id)).await;
+
+    // The old session is gone, so the token starts a fresh player
+    let alice = TestClient::login(server.tcp_addr, "Alice", Role::Participant, Some(token)).await;
+    assert_ne!(alice.id, id);
+}
+
+#[tokio::test]
+async fn rounds_run_over_http_reach_the_terminal_clients() {
+    let server = TestServer::start().await;
+    let mut alice = server.join("Alice", Role::Participant).await;
+    let mut bob = server.join("Bob", Role::Participant).await;
+
+    let (status, _) = server.post("/api/start-voting", json!({ "issue_number": "PROJ-7", "title": "Export CSV" })).await;
+    assert_eq!(status, 200);
+    alice.expect("the round starting", |p| matches!(p, ServerPayload::StateUpdate(s) if matches!(s.phase, Phase::Voting { .. }))).await;
+    let ticket = alice.state().current_ticket.clone().unwrap();
+    assert_eq!((ticket.title.as_str(), ticket.key.as_deref()), ("Export CSV", Some("PROJ-7")));
+
+    alice.vote_for(3);
+    alice.sync().await;
+    bob.vote_for(5);
+    bob.sync().await;
+
+    let (status, body) = server.post("/api/reveal", json!({})).await;
+    assert_eq!(status, 200);
+    let mut votes: Vec<(String, u64)> = body["votes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|v| (v["player_name"].as_str().unwrap().to_string(), v["vote"].as_u64().unwrap()))
+        .collect();
+    votes.sort();
+    assert_eq!(votes, vec![("Alice".to_string(), 3), ("Bob".to_string(), 5)]);
+
+    alice.expect("snapshot after the reveal", |p| matches!(p, ServerPayload::StateUpdate(_))).await;
+    assert_eq!(alice.state().votes.get(&bob.id), Some(&Some(5)));
+
+    // Kicking by name over HTTP drops the terminal player for everyone else
+    let (status, _) = server.post("/api/kick", json!({ "name": "Bob" })).await;
+    assert_eq!(status, 200);
+    alice.expect_event("Bob being kicked", |e| matches!(e, StateEvent::PlayerLeft { player_id } if *player_id == bob.id)).await;
+    bob.expect_error(ErrorCode::Kicked).await;
+    bob.expect_closed().await;
+}
+
+#[tokio::test]
+async fn floods_are_throttled_until_the_bucket_refills() {
+    let server = TestServer::with_settings(|s| {
+        s.rate_limit_per_sec = 10;
+        s.rate_limit_burst = 3;
+    })
+    .await;
+    let mut alice = server.join("Alice", Role::Participant).await;
+
+    for _ in 0..10 {
+        alice.move_to((2, 2));
+    }
+    alice.expect_error(ErrorCode::RateLimited).await;
+
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    alice.sync().await;
+}
+
+#[tokio::test]
+async fn oversized_frames_are_refused() {
+    let server = TestServer::start().await;
+    let mut alice = server.join("Alice", Role::Participant).await;
+
+    alice.send(common::ClientPayload::Login {
+        name: "x".repeat(common::MAX_FRAME_BYTES),
+        role: Role::Participant,
+        color: common::AvatarColor::Red,
+        symbol: common::AvatarSymbol::Human,
+        resume_token: None,
+    });
+    alice.expect_error(ErrorCode::FrameTooLarge).await;
+}
+
+#[tokio::test]
+async fn the_largest_valid_backlog_fits_in_a_frame() {
+    let server = TestServer::start().await;
+    let mut sam = server.join("Sam", Role::ScrumMaster).await;
+
+    // Control characters serialize to six-byte escapes, the worst case per character
+    let field = |len: usize| "\u{1}".repeat(len);
+    let ticket = common::Ticket {
+        key: Some(field(common::MAX_TICKET_KEY_LEN)),
+        description: Some(field(common::MAX_TICKET_DESCRIPTION_LEN)),
+        url: Some(field(common::MAX_TICKET_URL_LEN)),
+        labels: vec![field(common::MAX_TICKET_LABEL_LEN); common::MAX_TICKET_LABELS],
+        source: Some(common::TrackerKind::Github),
+        ..common::Ticket::new(field(common::MAX_TICKET_TITLE_LEN))
+    };
+    let tickets = vec![ticket; common::MAX_BACKLOG];
+    let frame = serde_json::to_string(&common::ClientPayload::Admin(AdminCommand::SetBacklog { tickets: tickets.clone() }));
+    assert!(frame.unwrap().len() > common::MAX_FRAME_BYTES * 9 / 10);
+
+    sam.admin(AdminCommand::SetBacklog { tickets });
+    sam.expect_event("BacklogChanged", |event| matches!(event, StateEvent::BacklogChanged(b) if b.len() == common::MAX_BACKLOG))
+        .await;
+}
+
+#[tokio::test]
+async fn metrics_count_the_traffic() {
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+    let server = TestServer::start().await;
+    let _sam = server.join("Sam", Role::ScrumMaster).await;
+    let mut alice = server.join("Alice", Role::Participant).await;
+    let (status, _) = server.post("/api/start-voting", json!({ "title": "Export CSV" })).await;
+    assert_eq!(status, 200);
+    alice.move_to((2, 2));
+    alice.sync().await;
+
+    let mut raw = BufReader::new(tokio::net::TcpStream::connect(server.tcp_addr).await.unwrap());
+    raw.get_mut().write_all(b"not json\n").await.unwrap();
+    let mut reply = String::new();
+    raw.read_line(&mut reply).await.unwrap();
+    assert!(reply.contains("MalformedMessage"), "{reply}");
+
+    let metrics = server.scrape().await;
+    let value = |name: &str| -> f64 {
+        let line = metrics.lines().find(|l| l.split(' ').next() == Some(name));
+        line.unwrap_or_else(|| panic!("no {name} in\n{metrics}")).rsplit(' ').next().unwrap().parse().unwrap()
+    };
+    assert_eq!(value("poker_connected_clients"), 3.0);
+    assert_eq!(value("poker_rooms"), 1.0);
+    assert_eq!(value("poker_rounds_started_total"), 1.0);
+    assert_eq!(value("poker_rounds_revealed_total"), 0.0);
+    assert_eq!(value("poker_parse_failures_total"), 1.0);
+    assert_eq!(value("poker_rate_limited_total"), 0.0);
+
+    // One series per payload variant, counted as they arrive
+    for kind in common::ClientPayload::KINDS {
+        value(&format!("poker_client_messages_total{{variant=\"{kind}\"}}"));
+    }
+    assert_eq!(value("poker_client_messages_total{variant=\"Login\"}"), 2.0);
+    assert_eq!(value("poker_client_messages_total{variant=\"Move\"}"), 1.0);
+    assert_eq!(value("poker_client_messages_total{variant=\"Vote\"}"), 0.0);
+
+    // Cumulative buckets ending in +Inf, which matches the count
+    let buckets: Vec<f64> = metrics
+        .lines()
+        .filter(|l| l.starts_with("poker_broadcast_duration_seconds_bucket{le="))
+        .map(|l| l.rsplit(' ').next().unwrap().parse().unwrap())
+        .collect();
+    assert!(buckets.len() > 1);
+    assert!(buckets.windows(2).all(|pair| pair[0] <= pair[1]), "{buckets:?}");
+    let count = value("poker_broadcast_duration_seconds_count");
+    assert!(count > 0.0);
+    assert_eq!(value("poker_broadcast_duration_seconds_bucket{le=\"+Inf\"}"), count);
+    assert!(value("poker_broadcast_duration_seconds_sum") >= 0.0);
+}
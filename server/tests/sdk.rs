@@ -0,0 +1,80 @@
+// The client SDK against a real server: what a bot or script would do.
+
+mod support;
+
+use common::{ErrorCode, Phase, Role, Ticket, MAX_SERVER_FRAME_BYTES};
+use sdk::{Connection, Error, Login, Session};
+use support::{TestServer, TIMEOUT};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+#[tokio::test]
+async fn a_scripted_round_from_start_to_reveal() {
+    let server = TestServer::start().await;
+    let mut sam = Session::login(server.tcp_addr, Login::new("Sam", Role::ScrumMaster)).await.unwrap();
+    let mut bot = Session::login(server.tcp_addr, Login::new("Bot", Role::Participant)).await.unwrap();
+
+    sam.start_round(None, Some(60)).unwrap();
+    let voting = |s: &common::GameState| matches!(s.phase, Phase::Voting { .. });
+    tokio::time::timeout(TIMEOUT, bot.wait_for(voting)).await.unwrap().unwrap();
+
+    bot.vote(Some(8)).unwrap();
+    bot.confirm(true).unwrap();
+    bot.sync().await.unwrap();
+    assert_eq!(bot.state().votes.get(&bot.self_id), Some(&Some(8)));
+    assert!(bot.me().unwrap().confirmed);
+
+    sam.reveal().unwrap();
+    let revealed = |s: &common::GameState| s.phase == Phase::Revealed;
+    tokio::time::timeout(TIMEOUT, sam.wait_for(revealed)).await.unwrap().unwrap();
+    assert_eq!(sam.state().votes.get(&bot.self_id), Some(&Some(8)));
+}
+
+#[tokio::test]
+async fn rejections_come_back_as_errors() {
+    let server = TestServer::start().await;
+    let mut bot = Session::login(server.tcp_addr, Login::new("Bot", Role::Participant)).await.unwrap();
+
+    let taken = Session::login(server.tcp_addr, Login::new("Bot", Role::Participant)).await;
+    assert!(matches!(taken, Err(Error::Rejected { code: ErrorCode::NameTaken, .. })));
+
+    bot.reveal().unwrap();
+    let refused = bot.sync().await;
+    assert!(matches!(refused, Err(Error::Rejected { code: ErrorCode::NotAuthorized, .. })));
+}
+
+// Snapshots and `BacklogChanged` carry the whole backlog in one frame.
+#[tokio::test]
+async fn large_backlogs_reach_every_session() {
+    let server = TestServer::start().await;
+    let mut bot = Session::login(server.tcp_addr, Login::new("Bot", Role::Participant)).await.unwrap();
+
+    let backlog: Vec<Ticket> = (0..20)
+        .map(|i| Ticket { description: Some("So much detail. ".repeat(60)), ..Ticket::new(format!("Ticket {i}")) })
+        .collect();
+    let body = serde_json::to_value(&backlog).unwrap();
+    let (status, _) = server.call(reqwest::Method::PUT, "/api/backlog", body).await;
+    assert_eq!(status, 200);
+
+    tokio::time::timeout(TIMEOUT, bot.wait_for(|s| s.backlog.len() == backlog.len())).await.unwrap().unwrap();
+    assert_eq!(bot.state().backlog, backlog);
+
+    let late = Session::login(server.tcp_addr, Login::new("Late", Role::Observer)).await.unwrap();
+    assert_eq!(late.state().backlog, backlog);
+}
+
+#[tokio::test]
+async fn an_unreadable_server_frame_is_an_error_not_a_silent_hang_up() {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        let (mut socket, _) = listener.accept().await.unwrap();
+        let _ = socket.write_all(&vec![b'x'; MAX_SERVER_FRAME_BYTES + 1]).await;
+        // Hold the socket open until the client gives up
+        let _ = socket.read(&mut [0; 1]).await;
+    });
+
+    let mut conn = Connection::connect(addr).await.unwrap();
+    let error = tokio::time::timeout(TIMEOUT, conn.recv()).await.unwrap();
+    assert!(matches!(error, Err(Error::Protocol(_))), "{error:?}");
+    assert!(matches!(conn.recv().await, Err(Error::Closed)));
+}
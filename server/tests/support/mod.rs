@@ -0,0 +1,215 @@
+// In-process server and a scriptable client for the end-to-end tests.
+
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use common::{AdminCommand, ClientPayload, ErrorCode, GameState, Role, ServerPayload, StateEvent};
+use sdk::{Login, Session};
+use server::Settings;
+use uuid::Uuid;
+
+// How long to wait for a message the test expects before failing.
+pub const TIMEOUT: Duration = Duration::from_secs(5);
+
+// What `TestServer::call` authenticates with.
+const ADMIN_TOKEN: &str = "e2e-admin";
+
+// A server on ephemeral ports with its files under a fresh temp dir. Dropping
+// it aborts the server task.
+pub struct TestServer {
+    pub tcp_addr: SocketAddr,
+    pub http_addr: SocketAddr,
+    http: reqwest::Client,
+    settings: Settings,
+    shutdown: Option<tokio::sync::oneshot::Sender<()>>,
+    task: tokio::task::JoinHandle<std::io::Result<()>>,
+}
+
+impl TestServer {
+    pub async fn start() -> Self {
+        Self::with_settings(|_| {}).await
+    }
+
+    pub async fn with_settings(tweak: impl FnOnce(&mut Settings)) -> Self {
+        let dir = std::env::temp_dir().join(format!("poker-e2e-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut settings = Settings {
+            snapshot_path: dir.join("room_snapshot.json"),
+            audit_path: dir.join("audit.log"),
+            config_path: dir.join("config.json"),
+            admin_token: Some(ADMIN_TOKEN.to_string()),
+            ..Settings::default()
+        };
+        tweak(&mut settings);
+        Self::launch(settings).await
+    }
+
+    async fn launch(settings: Settings) -> Self {
+        let server = server::start(settings.clone(), "127.0.0.1:0", "127.0.0.1:0").await.unwrap();
+        let (tcp_addr, http_addr) = (server.tcp_addr, server.http_addr);
+        let (shutdown, stopped) = tokio::sync::oneshot::channel();
+        let task = tokio::spawn(server.run_until(async {
+            let _ = stopped.await;
+        }));
+        Self { tcp_addr, http_addr, http: reqwest::Client::new(), settings, shutdown: Some(shutdown), task }
+    }
+
+    // Sends `body` to an HTTP API path as an admin, and returns the status and JSON reply.
+    pub async fn call(&self, method: reqwest::Method, path: &str, body: serde_json::Value) -> (u16, serde_json::Value) {
+        let response = self
+            .http
+            .request(method, format!("http://{}{}", self.http_addr, path))
+            .header("x-poker-actor", "e2e")
+            .bearer_auth(ADMIN_TOKEN)
+            .json(&body)
+            .send()
+            .await
+            .unwrap();
+        let status = response.status().as_u16();
+        (status, response.json().await.unwrap_or_default())
+    }
+}
+
+// The scripted players and HTTP calls e2e.rs is built on; sdk.rs talks to the
+// server through `sdk::Session` directly.
+#[allow(dead_code)]
+impl TestServer {
+    pub async fn join(&self, name: &str, role: Role) -> TestClient {
+        TestClient::login(self.tcp_addr, name, role, None).await
+    }
+
+    pub async fn post(&self, path: &str, body: serde_json::Value) -> (u16, serde_json::Value) {
+        self.call(reqwest::Method::POST, path, body).await
+    }
+
+    // The Prometheus text from `/metrics`.
+    pub async fn scrape(&self) -> String {
+        let response = self.http.get(format!("http://{}/metrics", self.http_addr)).send().await.unwrap();
+        assert!(response.status().is_success());
+        response.text().await.unwrap()
+    }
+
+    // Shuts the server down the way a signal would, which saves the room, then
+    // starts a new one on the same files. The new one listens on new ports.
+    pub async fn restart(mut self) -> Self {
+        let _ = self.shutdown.take().unwrap().send(());
+        (&mut self.task).await.unwrap().unwrap();
+        Self::launch(self.settings.clone()).await
+    }
+}
+
+impl Drop for TestServer {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+// One simulated player on its own connection. A thin layer over
+// `sdk::Session` that fails the test instead of returning errors.
+#[allow(dead_code)]
+pub struct TestClient {
+    pub id: Uuid,
+    pub session_token: Uuid,
+    session: Session,
+}
+
+#[allow(dead_code)]
+impl TestClient {
+    pub async fn login(addr: SocketAddr, name: &str, role: Role, resume_token: Option<Uuid>) -> Self {
+        let login = Login { resume_token, ..Login::new(name, role) };
+        let session = Session::login(addr, login).await.unwrap_or_else(|e| panic!("{name}: login failed: {e}"));
+        Self { id: session.self_id, session_token: session.session_token, session }
+    }
+
+    // The room as this player sees it, as of the last message received.
+    pub fn state(&self) -> &GameState {
+        self.session.state()
+    }
+
+    pub fn send(&mut self, payload: ClientPayload) {
+        self.session.connection().send(payload).unwrap();
+    }
+
+    pub fn move_to(&mut self, (x, y): (u16, u16)) {
+        self.session.move_to(x, y).unwrap();
+    }
+
+    // Walks into the centre of `card`'s zone, which is how a vote is cast.
+    pub fn vote_for(&mut self, card: u32) {
+        assert!(self.state().map.zone_for_card(card).is_some(), "no zone for {card}");
+        self.session.walk_to_card(card).unwrap();
+    }
+
+    pub fn confirm(&mut self) {
+        self.session.confirm(true).unwrap();
+    }
+
+    pub fn admin(&mut self, cmd: AdminCommand) {
+        self.session.admin(cmd).unwrap();
+    }
+
+    // The next message, already applied to `state()`. Panics after `TIMEOUT`.
+    pub async fn recv(&mut self) -> ServerPayload {
+        match tokio::time::timeout(TIMEOUT, self.session.next()).await {
+            Ok(payload) => payload.unwrap(),
+            Err(_) => panic!("timed out waiting for a message"),
+        }
+    }
+
+    // Skips messages until one matches, and returns it.
+    pub async fn expect(&mut self, what: &str, mut matches: impl FnMut(&ServerPayload) -> bool) -> ServerPayload {
+        let found = tokio::time::timeout(TIMEOUT, async {
+            loop {
+                let payload = self.session.next().await.unwrap();
+                if matches(&payload) {
+                    return payload;
+                }
+            }
+        });
+        found.await.unwrap_or_else(|_| panic!("timed out waiting for {what}"))
+    }
+
+    // Skips messages until a batch of deltas contains one that matches.
+    pub async fn expect_event(&mut self, what: &str, mut matches: impl FnMut(&StateEvent) -> bool) {
+        self.expect(what, |payload| match payload {
+            ServerPayload::Events(events) => events.iter().any(&mut matches),
+            _ => false,
+        })
+        .await;
+    }
+
+    pub async fn expect_error(&mut self, code: ErrorCode) {
+        self.expect(&format!("{code:?} error"), |payload| {
+            matches!(payload, ServerPayload::Error { code: c, .. } if *c == code)
+        })
+        .await;
+    }
+
+    // Skips messages until the server hangs up.
+    pub async fn expect_closed(&mut self) {
+        let closed = tokio::time::timeout(TIMEOUT, async {
+            loop {
+                match self.session.next().await {
+                    Ok(_) => continue,
+                    Err(sdk::Error::Closed) => return,
+                    Err(e) => panic!("expected the connection to close, got {e}"),
+                }
+            }
+        });
+        closed.await.unwrap_or_else(|_| panic!("timed out waiting for the server to hang up"));
+    }
+
+    // Round-trips a ping and returns what arrived before the pong. The room
+    // handles messages in order, so once a client has synced, everything its
+    // earlier messages caused has been sent out to every connection.
+    pub async fn sync(&mut self) -> Vec<ServerPayload> {
+        let nonce = self.session.connection().ping().unwrap();
+        let mut received = Vec::new();
+        loop {
+            match self.recv().await {
+                ServerPayload::Pong { nonce: n } if n == nonce => return received,
+                other => received.push(other),
+            }
+        }
+    }
+}